@@ -0,0 +1,74 @@
+//! Compares rendering a scene of many small spheres with and without a
+//! bounding volume hierarchy. Run with `cargo bench --bench bvh`.
+
+use std::{f64::consts::PI, time::Instant};
+
+use ray_tracer_challenge::{
+    camera::Camera,
+    canvas::Color,
+    lights::{Light, PointLight},
+    shapes::{Plane, Shape, Sphere},
+    transformations::{view_transform, Builder},
+    world::World,
+    Point, Vector,
+};
+
+const GRID: usize = 40;
+
+fn scene() -> World {
+    let mut world = World::new();
+    world.lights = vec![Light::new(PointLight::new(
+        Point::new(-10.0, 10.0, -10.0),
+        Color::new(1.0, 1.0, 1.0),
+    ))];
+    world.add_object(Shape::new(Plane));
+    for i in 0..GRID {
+        for j in 0..GRID {
+            let mut sphere = Shape::new(Sphere);
+            sphere
+                .set_transform(
+                    Builder::new()
+                        .scaling(0.2, 0.2, 0.2)
+                        .translation(i as f64 * 0.5 - 10.0, 0.2, j as f64 * 0.5)
+                        .transform(),
+                )
+                .expect("no inverse for transform");
+            sphere.material.color = Color::new(i as f64 / GRID as f64, 0.5, j as f64 / GRID as f64);
+            world.add_object(sphere);
+        }
+    }
+    world
+}
+
+fn main() {
+    let mut camera = Camera::new(160, 90, PI / 3.0);
+    camera
+        .set_transform(view_transform(
+            Point::new(0.0, 3.0, -6.0),
+            Point::new(0.0, 0.0, 5.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ))
+        .expect("no inverse error");
+
+    let mut linear = scene();
+    linear.set_bvh_enabled(false);
+    let accelerated = scene();
+
+    let start = Instant::now();
+    let expected = camera.render(&linear);
+    let linear_time = start.elapsed();
+
+    let start = Instant::now();
+    let image = camera.render(&accelerated);
+    let bvh_time = start.elapsed();
+
+    assert_eq!(image, expected, "BVH changed the rendered image");
+
+    println!("objects: {}", linear.objects().len());
+    println!("linear:  {linear_time:?}");
+    println!("bvh:     {bvh_time:?}");
+    println!(
+        "speedup: {:.1}x",
+        linear_time.as_secs_f64() / bvh_time.as_secs_f64()
+    );
+}
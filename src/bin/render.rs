@@ -1,10 +1,10 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::f64::consts::PI;
 
 use ray_tracer_challenge::{
     camera::Camera,
     canvas::Color,
-    lights::PointLight,
+    lights::{AreaLight, Light, PointLight},
     materials::Material,
     patterns::{Checkers, Gradient, Pattern, Rings},
     shapes::{Plane, Shape, Sphere},
@@ -23,6 +23,51 @@ struct Args {
 
     #[arg(long, default_value = "270")]
     height: usize,
+
+    #[arg(long, value_enum, default_value = "perspective")]
+    projection: Projection,
+
+    /// Field of view in degrees, for perspective and fisheye projections
+    #[arg(long, default_value = "60")]
+    field_of_view: f64,
+
+    /// Width of the view in world units, for the orthographic projection
+    #[arg(long, default_value = "8")]
+    view_size: f64,
+
+    /// Rays averaged into each pixel, for anti-aliasing
+    #[arg(long, default_value = "1")]
+    samples: usize,
+
+    /// Refine only pixels that differ from a neighbor by more than this,
+    /// instead of sampling every pixel
+    #[arg(long)]
+    adaptive: Option<f64>,
+
+    /// Radius of the camera lens, for depth of field
+    #[arg(long, default_value = "0")]
+    aperture: f64,
+
+    /// Distance from the camera to the plane in focus
+    #[arg(long, default_value = "5")]
+    focal_distance: f64,
+
+    /// Replace the point light with a square area light of this many cells
+    /// along each side, for soft shadows
+    #[arg(long)]
+    area_light: Option<usize>,
+
+    /// Number of worker threads, defaulting to one per available core
+    #[arg(long)]
+    threads: Option<usize>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Projection {
+    Perspective,
+    Orthographic,
+    Equirectangular,
+    Fisheye,
 }
 
 fn main() -> std::io::Result<()> {
@@ -118,10 +163,37 @@ fn main() -> std::io::Result<()> {
     left.material.reflective = 0.2;
 
     let mut world = World::new();
-    world.objects = vec![floor, back_wall, middle, right, left];
-    world.light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    for object in [floor, back_wall, middle, right, left] {
+        world.add_object(object);
+    }
+    let light = match args.area_light {
+        Some(cells) => Light::new(
+            AreaLight::new(
+                Point::new(-11.0, 10.0, -11.0),
+                Vector::new(2.0, 0.0, 0.0),
+                cells,
+                Vector::new(0.0, 0.0, 2.0),
+                cells,
+                Color::new(1.0, 1.0, 1.0),
+            )
+            .expect("area light needs at least one cell"),
+        ),
+        None => Light::new(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        )),
+    };
+    world.lights = vec![light];
 
-    let mut camera = Camera::new(args.width, args.height, PI / 3.0);
+    let field_of_view = args.field_of_view.to_radians();
+    let mut camera = match args.projection {
+        Projection::Perspective => Camera::new(args.width, args.height, field_of_view),
+        Projection::Orthographic => {
+            Camera::new_orthographic(args.width, args.height, args.view_size)
+        }
+        Projection::Equirectangular => Camera::new_equirectangular(args.width, args.height),
+        Projection::Fisheye => Camera::new_fisheye(args.width, args.height, field_of_view),
+    };
     camera
         .set_transform(view_transform(
             Point::new(0.0, 1.5, -5.0),
@@ -129,8 +201,18 @@ fn main() -> std::io::Result<()> {
             Vector::new(0.0, 1.0, 0.0),
         ))
         .expect("no inverse error");
+    camera
+        .set_samples_per_pixel(args.samples)
+        .expect("samples must be at least 1");
+    camera.set_adaptive_threshold(args.adaptive);
+    camera
+        .set_depth_of_field(args.aperture, args.focal_distance)
+        .expect("aperture must not be negative and focal distance must be positive");
 
-    let canvas = camera.render(&world);
+    let threads = args
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+    let canvas = camera.render_parallel(&world, threads);
 
     std::fs::write(args.output, canvas.to_ppm())?;
 
@@ -0,0 +1,403 @@
+use crate::{matrices::Transform, rays::Ray, Point, Vector};
+
+/// An axis-aligned bounding box, possibly infinite along some axes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> Self {
+        BoundingBox { min, max }
+    }
+
+    /// A box containing nothing, which grows to fit whatever is added to it
+    pub fn empty() -> Self {
+        BoundingBox {
+            min: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn infinite() -> Self {
+        BoundingBox {
+            min: Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [
+            self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z,
+        ]
+        .iter()
+        .all(|value| value.is_finite())
+    }
+
+    pub fn add_point(&mut self, point: Point) {
+        self.min = Point::new(
+            self.min.x.min(point.x),
+            self.min.y.min(point.y),
+            self.min.z.min(point.z),
+        );
+        self.max = Point::new(
+            self.max.x.max(point.x),
+            self.max.y.max(point.y),
+            self.max.z.max(point.z),
+        );
+    }
+
+    pub fn merge(&mut self, other: &BoundingBox) {
+        if !other.is_empty() {
+            self.add_point(other.min);
+            self.add_point(other.max);
+        }
+    }
+
+    pub fn contains_point(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    /// Returns the smallest axis-aligned box containing this box after it
+    /// has been transformed
+    pub fn transformed(&self, transform: &Transform) -> BoundingBox {
+        if self.is_empty() {
+            return *self;
+        }
+
+        // Arvo's method: each output extent is the translation plus the sum
+        // of the smallest (or largest) products along each input axis. This
+        // avoids transforming infinite corners, where 0 * inf would give NaN.
+        let translation = transform * Point::new(0.0, 0.0, 0.0);
+        let linear = transform.submatrix(3, 3).expect("matrix index error");
+        let min = [self.min.x, self.min.y, self.min.z];
+        let max = [self.max.x, self.max.y, self.max.z];
+        let mut new_min = [translation.x, translation.y, translation.z];
+        let mut new_max = new_min;
+        for i in 0..3 {
+            for j in 0..3 {
+                let factor = linear[[i, j]];
+                if factor == 0.0 {
+                    continue;
+                }
+                let a = factor * min[j];
+                let b = factor * max[j];
+                new_min[i] += a.min(b);
+                new_max[i] += a.max(b);
+            }
+        }
+
+        BoundingBox {
+            min: Point::new(new_min[0], new_min[1], new_min[2]),
+            max: Point::new(new_max[0], new_max[1], new_max[2]),
+        }
+    }
+
+    /// Whether the line along `ray` passes through the box, including behind
+    /// the ray's origin
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        tmin <= tmax
+    }
+
+    pub fn size(&self) -> Vector {
+        self.max - self.min
+    }
+}
+
+impl Default for BoundingBox {
+    fn default() -> Self {
+        BoundingBox::empty()
+    }
+}
+
+/// Returns the t values where a ray enters and leaves the slab between `min`
+/// and `max` along a single axis. Nearly parallel rays are left to infinite t
+/// values, since they may still reach the slab far away.
+fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    if direction == 0.0 {
+        // The ray never leaves the slab if it starts inside it, and never
+        // enters it otherwise
+        if (min..=max).contains(&origin) {
+            (f64::NEG_INFINITY, f64::INFINITY)
+        } else {
+            (f64::INFINITY, f64::NEG_INFINITY)
+        }
+    } else {
+        let tmin = (min - origin) / direction;
+        let tmax = (max - origin) / direction;
+        if tmin > tmax {
+            (tmax, tmin)
+        } else {
+            (tmin, tmax)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use crate::transformations::{rotation_x, rotation_y, translation};
+
+    use super::*;
+
+    #[test]
+    fn create_empty_box() {
+        let b = BoundingBox::empty();
+        assert!(b.is_empty());
+        assert_eq!(b.min.x, f64::INFINITY);
+        assert_eq!(b.max.x, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn create_box_with_volume() {
+        let b = BoundingBox::new(Point::new(-1.0, -2.0, -3.0), Point::new(3.0, 2.0, 1.0));
+        assert_eq!(b.min, Point::new(-1.0, -2.0, -3.0));
+        assert_eq!(b.max, Point::new(3.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn add_points_to_empty_box() {
+        let mut b = BoundingBox::empty();
+        b.add_point(Point::new(-5.0, 2.0, 0.0));
+        b.add_point(Point::new(7.0, 0.0, -3.0));
+        assert_eq!(b.min, Point::new(-5.0, 0.0, -3.0));
+        assert_eq!(b.max, Point::new(7.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn merge_boxes() {
+        let mut b1 = BoundingBox::new(Point::new(-5.0, -2.0, 0.0), Point::new(7.0, 4.0, 4.0));
+        let b2 = BoundingBox::new(Point::new(8.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+        b1.merge(&b2);
+        assert_eq!(b1.min, Point::new(-5.0, -7.0, -2.0));
+        assert_eq!(b1.max, Point::new(14.0, 4.0, 8.0));
+    }
+
+    #[test]
+    fn merge_empty_box() {
+        let mut b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        b.merge(&BoundingBox::empty());
+        assert_eq!(
+            b,
+            BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn box_contains_point() {
+        let b = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+        let examples = [
+            (Point::new(5.0, -2.0, 0.0), true),
+            (Point::new(11.0, 4.0, 7.0), true),
+            (Point::new(8.0, 1.0, 3.0), true),
+            (Point::new(3.0, 0.0, 3.0), false),
+            (Point::new(8.0, -4.0, 3.0), false),
+            (Point::new(8.0, 1.0, -1.0), false),
+            (Point::new(13.0, 1.0, 3.0), false),
+            (Point::new(8.0, 5.0, 3.0), false),
+            (Point::new(8.0, 1.0, 8.0), false),
+        ];
+        for (point, result) in examples {
+            assert_eq!(b.contains_point(point), result);
+        }
+    }
+
+    #[test]
+    fn box_contains_box() {
+        let b = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+        let examples = [
+            (Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0), true),
+            (Point::new(6.0, -1.0, 1.0), Point::new(10.0, 3.0, 6.0), true),
+            (
+                Point::new(4.0, -3.0, -1.0),
+                Point::new(10.0, 3.0, 6.0),
+                false,
+            ),
+            (
+                Point::new(6.0, -1.0, 1.0),
+                Point::new(12.0, 5.0, 8.0),
+                false,
+            ),
+        ];
+        for (min, max, result) in examples {
+            assert_eq!(b.contains_box(&BoundingBox::new(min, max)), result);
+        }
+    }
+
+    #[test]
+    fn transform_box() {
+        let b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let matrix = &rotation_x(PI / 4.0) * &rotation_y(PI / 4.0);
+        let b2 = b.transformed(&matrix);
+        assert_eq!(b2.min, Point::new(-(2_f64.sqrt()), -1.70711, -1.70711));
+        assert_eq!(b2.max, Point::new(2_f64.sqrt(), 1.70711, 1.70711));
+    }
+
+    #[test]
+    fn transform_infinite_box() {
+        let b = BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        );
+        let b2 = b.transformed(&translation(1.0, 2.0, 3.0));
+        assert_eq!(b2.min.x, f64::NEG_INFINITY);
+        assert_eq!(b2.min.y, 2.0);
+        assert_eq!(b2.max.y, 2.0);
+        assert_eq!(b2.max.z, f64::INFINITY);
+
+        let b3 = b.transformed(&rotation_x(PI / 2.0));
+        assert_eq!(b3.min.x, f64::NEG_INFINITY);
+        assert_eq!(b3.max.x, f64::INFINITY);
+        assert_eq!(b3.min.y, f64::NEG_INFINITY);
+        assert_eq!(b3.max.y, f64::INFINITY);
+        assert!(!b3.min.x.is_nan() && !b3.min.z.is_nan());
+    }
+
+    #[test]
+    fn intersect_ray_with_cubic_box() {
+        let b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let examples = [
+            (Point::new(5.0, 0.5, 0.0), Vector::new(-1.0, 0.0, 0.0), true),
+            (Point::new(-5.0, 0.5, 0.0), Vector::new(1.0, 0.0, 0.0), true),
+            (Point::new(0.5, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0), true),
+            (Point::new(0.5, -5.0, 0.0), Vector::new(0.0, 1.0, 0.0), true),
+            (Point::new(0.5, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0), true),
+            (Point::new(0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), true),
+            (Point::new(0.0, 0.5, 0.0), Vector::new(0.0, 0.0, 1.0), true),
+            (
+                Point::new(-2.0, 0.0, 0.0),
+                Vector::new(2.0, 4.0, 6.0),
+                false,
+            ),
+            (
+                Point::new(0.0, -2.0, 0.0),
+                Vector::new(6.0, 2.0, 4.0),
+                false,
+            ),
+            (
+                Point::new(0.0, 0.0, -2.0),
+                Vector::new(4.0, 6.0, 2.0),
+                false,
+            ),
+            (
+                Point::new(2.0, 0.0, 2.0),
+                Vector::new(0.0, 0.0, -1.0),
+                false,
+            ),
+            (
+                Point::new(0.0, 2.0, 2.0),
+                Vector::new(0.0, -1.0, 0.0),
+                false,
+            ),
+            (
+                Point::new(2.0, 2.0, 0.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                false,
+            ),
+        ];
+        for (origin, direction, result) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            assert_eq!(b.intersects(&r), result);
+        }
+    }
+
+    #[test]
+    fn intersect_ray_with_noncubic_box() {
+        let b = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+        let examples = [
+            (
+                Point::new(15.0, 1.0, 2.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                true,
+            ),
+            (
+                Point::new(-5.0, -1.0, 4.0),
+                Vector::new(1.0, 0.0, 0.0),
+                true,
+            ),
+            (Point::new(7.0, 6.0, 5.0), Vector::new(0.0, -1.0, 0.0), true),
+            (Point::new(9.0, -5.0, 6.0), Vector::new(0.0, 1.0, 0.0), true),
+            (
+                Point::new(8.0, 2.0, 12.0),
+                Vector::new(0.0, 0.0, -1.0),
+                true,
+            ),
+            (Point::new(6.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), true),
+            (Point::new(8.0, 1.0, 3.5), Vector::new(0.0, 0.0, 1.0), true),
+            (
+                Point::new(9.0, -1.0, -8.0),
+                Vector::new(2.0, 4.0, 6.0),
+                false,
+            ),
+            (
+                Point::new(8.0, 3.0, -4.0),
+                Vector::new(6.0, 2.0, 4.0),
+                false,
+            ),
+            (
+                Point::new(9.0, -1.0, -2.0),
+                Vector::new(4.0, 6.0, 2.0),
+                false,
+            ),
+            (
+                Point::new(4.0, 0.0, 9.0),
+                Vector::new(0.0, 0.0, -1.0),
+                false,
+            ),
+            (
+                Point::new(8.0, 6.0, -1.0),
+                Vector::new(0.0, -1.0, 0.0),
+                false,
+            ),
+            (
+                Point::new(12.0, 5.0, 4.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                false,
+            ),
+        ];
+        for (origin, direction, result) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            assert_eq!(b.intersects(&r), result);
+        }
+    }
+
+    #[test]
+    fn intersect_ray_with_infinite_box() {
+        let b = BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        );
+        let hit = Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let miss = Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert!(b.intersects(&hit));
+        assert!(!b.intersects(&miss));
+    }
+
+    #[test]
+    fn intersect_grazing_ray_with_long_box() {
+        let b = BoundingBox::new(Point::new(0.0, 0.0, 0.0), Point::new(1e6, 1.0, 1.0));
+        let r = Ray::new(Point::new(0.0, 1.5, 0.5), Vector::new(1.0, -1e-6, 0.0));
+        assert!(b.intersects(&r));
+        let r = Ray::new(Point::new(0.0, 1.5, 0.5), Vector::new(1.0, 1e-6, 0.0));
+        assert!(!b.intersects(&r));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{bounds::BoundingBox, rays::Ray, Point, Vector};
+
+/// Primitives per leaf below which nodes are never split
+const MIN_SPLIT_SIZE: usize = 2;
+/// Number of buckets the surface area heuristic evaluates along each axis
+const SAH_BUCKETS: usize = 12;
+/// Cost of testing a ray against a node's box, relative to testing a primitive
+const TRAVERSAL_COST: f64 = 0.125;
+/// Boxes are padded so rounding never culls a primitive the ray grazes
+const PADDING: f64 = 0.00001;
+
+/// A bounding volume hierarchy over a list of primitives, identified by their
+/// index, used to skip primitives whose bounds a ray misses
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Primitives in leaf order, so each leaf refers to a contiguous range
+    primitives: Vec<Primitive>,
+    /// Primitives with infinite bounds, which are always candidates
+    unbounded: Vec<usize>,
+    bounds: BoundingBox,
+    len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    bounds: BoundingBox,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NodeKind {
+    Leaf { start: usize, end: usize },
+    Interior { left: usize, right: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Primitive {
+    index: usize,
+    bounds: BoundingBox,
+    centroid: Point,
+}
+
+impl Bvh {
+    /// Builds a hierarchy over primitives with the given bounds, splitting
+    /// nodes with the surface area heuristic
+    pub fn new(bounds: &[BoundingBox]) -> Self {
+        let mut bvh = Bvh {
+            len: bounds.len(),
+            ..Bvh::default()
+        };
+
+        let mut primitives = vec![];
+        for (index, primitive_bounds) in bounds.iter().enumerate() {
+            bvh.bounds.merge(primitive_bounds);
+            if primitive_bounds.is_empty() {
+                continue;
+            } else if !primitive_bounds.is_finite() {
+                bvh.unbounded.push(index);
+            } else {
+                let bounds = padded(primitive_bounds);
+                primitives.push(Primitive {
+                    index,
+                    bounds,
+                    centroid: bounds.min + bounds.size() * 0.5,
+                });
+            }
+        }
+
+        if !primitives.is_empty() {
+            bvh.build(&mut primitives, 0);
+        }
+        bvh.primitives = primitives;
+        bvh
+    }
+
+    /// Number of primitives the hierarchy was built over
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The combined bounds of every primitive
+    pub fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    /// Indices of every primitive whose bounds `ray` passes through, in
+    /// ascending order
+    pub fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        let mut candidates = self.unbounded.clone();
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !node.bounds.intersects(ray) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { start, end } => candidates.extend(
+                    self.primitives[start..end]
+                        .iter()
+                        .filter(|primitive| primitive.bounds.intersects(ray))
+                        .map(|primitive| primitive.index),
+                ),
+                NodeKind::Interior { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+
+        // Keeps results in the same order as testing every primitive in turn
+        candidates.sort_unstable();
+        candidates
+    }
+
+    /// Recursively adds nodes for `primitives`, which are reordered so each
+    /// leaf refers to a contiguous range, returning the index of the new node
+    fn build(&mut self, primitives: &mut [Primitive], offset: usize) -> usize {
+        let mut bounds = BoundingBox::empty();
+        for primitive in primitives.iter() {
+            bounds.merge(&primitive.bounds);
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            kind: NodeKind::Leaf {
+                start: offset,
+                end: offset + primitives.len(),
+            },
+        });
+
+        if primitives.len() <= MIN_SPLIT_SIZE {
+            return node;
+        }
+        let Some(split) = split_point(primitives, &bounds) else {
+            return node;
+        };
+
+        let (left_primitives, right_primitives) = primitives.split_at_mut(split);
+        let left = self.build(left_primitives, offset);
+        let right = self.build(right_primitives, offset + split);
+        self.nodes[node].kind = NodeKind::Interior { left, right };
+        node
+    }
+}
+
+fn padded(bounds: &BoundingBox) -> BoundingBox {
+    let padding = Vector::new(PADDING, PADDING, PADDING);
+    BoundingBox::new(bounds.min - padding, bounds.max + padding)
+}
+
+fn surface_area(bounds: &BoundingBox) -> f64 {
+    if bounds.is_empty() {
+        return 0.0;
+    }
+    let size = bounds.size();
+    2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+}
+
+fn axis_value(point: Point, axis: usize) -> f64 {
+    match axis {
+        0 => point.x,
+        1 => point.y,
+        _ => point.z,
+    }
+}
+
+/// Partitions `primitives` along the cheapest split found by the surface
+/// area heuristic, returning the size of the first partition, or `None` if
+/// no split is cheaper than a leaf
+fn split_point(primitives: &mut [Primitive], bounds: &BoundingBox) -> Option<usize> {
+    let mut centroid_bounds = BoundingBox::empty();
+    for primitive in primitives.iter() {
+        centroid_bounds.add_point(primitive.centroid);
+    }
+
+    let parent_area = surface_area(bounds);
+    let leaf_cost = primitives.len() as f64;
+    let mut best: Option<(f64, usize, f64)> = None;
+
+    for axis in 0..3 {
+        let min = axis_value(centroid_bounds.min, axis);
+        let max = axis_value(centroid_bounds.max, axis);
+        if max - min <= 0.0 {
+            continue;
+        }
+
+        let bucket_of = |primitive: &Primitive| {
+            let relative = (axis_value(primitive.centroid, axis) - min) / (max - min);
+            ((relative * SAH_BUCKETS as f64) as usize).min(SAH_BUCKETS - 1)
+        };
+
+        let mut counts = [0; SAH_BUCKETS];
+        let mut bucket_bounds = [BoundingBox::empty(); SAH_BUCKETS];
+        for primitive in primitives.iter() {
+            let bucket = bucket_of(primitive);
+            counts[bucket] += 1;
+            bucket_bounds[bucket].merge(&primitive.bounds);
+        }
+
+        for split in 1..SAH_BUCKETS {
+            let mut left = BoundingBox::empty();
+            let mut right = BoundingBox::empty();
+            let mut left_count = 0;
+            let mut right_count = 0;
+            for bucket in 0..SAH_BUCKETS {
+                if bucket < split {
+                    left.merge(&bucket_bounds[bucket]);
+                    left_count += counts[bucket];
+                } else {
+                    right.merge(&bucket_bounds[bucket]);
+                    right_count += counts[bucket];
+                }
+            }
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            let cost = TRAVERSAL_COST
+                + (surface_area(&left) * left_count as f64
+                    + surface_area(&right) * right_count as f64)
+                    / parent_area;
+            if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                let threshold = min + (max - min) * split as f64 / SAH_BUCKETS as f64;
+                best = Some((cost, axis, threshold));
+            }
+        }
+    }
+
+    let (cost, axis, threshold) = best?;
+    if cost >= leaf_cost {
+        return None;
+    }
+
+    // Partition in place around the chosen bucket boundary
+    let mut split = 0;
+    for index in 0..primitives.len() {
+        if axis_value(primitives[index].centroid, axis) < threshold {
+            primitives.swap(index, split);
+            split += 1;
+        }
+    }
+    if split == 0 || split == primitives.len() {
+        None
+    } else {
+        Some(split)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unit_box_at(x: f64, y: f64, z: f64) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(x - 1.0, y - 1.0, z - 1.0),
+            Point::new(x + 1.0, y + 1.0, z + 1.0),
+        )
+    }
+
+    #[test]
+    fn empty_bvh() {
+        let bvh = Bvh::new(&[]);
+        assert!(bvh.is_empty());
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(bvh.candidates(&r).is_empty());
+    }
+
+    #[test]
+    fn candidates_are_primitives_along_ray() {
+        let bounds = (0..100)
+            .map(|i| unit_box_at((i % 10) as f64 * 3.0, (i / 10) as f64 * 3.0, 0.0))
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(&bounds);
+        assert_eq!(bvh.len(), 100);
+
+        let r = Ray::new(Point::new(6.0, 9.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(bvh.candidates(&r), vec![32]);
+
+        let r = Ray::new(Point::new(-5.0, 3.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(bvh.candidates(&r), (10..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn candidates_match_brute_force() {
+        let bounds = (0..500)
+            .map(|i| {
+                let i = i as f64;
+                unit_box_at((i * 7.3) % 41.0, (i * 3.1) % 23.0, (i * 5.7) % 37.0)
+            })
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(&bounds);
+
+        for i in 0..50 {
+            let i = i as f64;
+            let r = Ray::new(
+                Point::new(-10.0, i % 20.0, i % 30.0),
+                Vector::new(1.0, (i * 0.37).sin(), (i * 0.71).cos()),
+            );
+            let expected = (0..bounds.len())
+                .filter(|&index| bounds[index].intersects(&r))
+                .collect::<Vec<_>>();
+            assert_eq!(bvh.candidates(&r), expected);
+        }
+    }
+
+    #[test]
+    fn unbounded_primitives_are_always_candidates() {
+        let plane = BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        );
+        let bvh = Bvh::new(&[unit_box_at(0.0, 5.0, 0.0), plane]);
+        let r = Ray::new(Point::new(0.0, 5.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(bvh.candidates(&r), vec![0, 1]);
+        assert!(!bvh.bounds().is_finite());
+    }
+
+    #[test]
+    fn bvh_splits_large_lists() {
+        let bounds = (0..64)
+            .map(|i| unit_box_at(i as f64 * 3.0, 0.0, 0.0))
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(&bounds);
+        assert!(bvh.nodes.len() > 1);
+        for node in &bvh.nodes {
+            if let NodeKind::Leaf { start, end } = node.kind {
+                assert!(end - start <= MIN_SPLIT_SIZE);
+            }
+        }
+    }
+}
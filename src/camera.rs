@@ -1,9 +1,16 @@
+use std::{
+    f64::consts::PI,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
 use crate::{
-    canvas::{Canvas, PixelOutOfBoundsError},
+    canvas::{Canvas, Color, PixelOutOfBoundsError},
     matrices::{Transform, IDENTITY},
+    random::Rng,
     rays::Ray,
     world::{World, RECURSION_DEPTH},
-    Point,
+    Point, Vector,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -11,19 +18,85 @@ pub struct Camera {
     hsize: usize,
     vsize: usize,
     field_of_view: f64,
+    projection: Projection,
     transform: Transform,
     inverse: Transform,
     half_width: f64,
     half_height: f64,
     pixel_size: f64,
+    samples_per_pixel: usize,
+    adaptive_threshold: Option<f64>,
+    aperture: f64,
+    focal_distance: f64,
+}
+
+/// How many times a pixel may be split into quadrants by adaptive sampling
+pub const ADAPTIVE_DEPTH: usize = 2;
+
+/// How the camera maps points on the canvas to rays
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Projection {
+    /// Rays fan out from the camera through a canvas one unit in front of it
+    #[default]
+    Perspective,
+    /// Rays run parallel to the view direction, each starting from its
+    /// point on a canvas measured in world units
+    Orthographic,
+    /// A full panorama, with longitude across the canvas and latitude down
+    /// it, so the view direction lands in the center
+    Equirectangular,
+    /// An angular fisheye, where a point's distance from the center of the
+    /// canvas is proportional to its angle from the view direction
+    Fisheye,
 }
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub struct NoInverseError;
 
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ZeroSamplesError;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidLensError;
+
 impl Camera {
     pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
         let half_view = (field_of_view / 2.0).tan();
+        Self::with_half_view(
+            hsize,
+            vsize,
+            half_view,
+            field_of_view,
+            Projection::Perspective,
+        )
+    }
+
+    /// Returns an orthographic camera whose longer side spans `view_size`
+    /// world units. It has no field of view, which reads as zero.
+    pub fn new_orthographic(hsize: usize, vsize: usize, view_size: f64) -> Self {
+        Self::with_half_view(hsize, vsize, view_size / 2.0, 0.0, Projection::Orthographic)
+    }
+
+    /// Returns a camera that sees in every direction. The canvas is usually
+    /// twice as wide as it is tall, to keep pixels square.
+    pub fn new_equirectangular(hsize: usize, vsize: usize) -> Self {
+        Self::with_half_view(hsize, vsize, PI, 2.0 * PI, Projection::Equirectangular)
+    }
+
+    /// Returns an angular fisheye camera whose longer side spans
+    /// `field_of_view`, which may exceed a half turn
+    pub fn new_fisheye(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        let half_view = field_of_view / 2.0;
+        Self::with_half_view(hsize, vsize, half_view, field_of_view, Projection::Fisheye)
+    }
+
+    fn with_half_view(
+        hsize: usize,
+        vsize: usize,
+        half_view: f64,
+        field_of_view: f64,
+        projection: Projection,
+    ) -> Self {
         let aspect = hsize as f64 / vsize as f64;
 
         let half_width;
@@ -42,30 +115,169 @@ impl Camera {
             hsize,
             vsize,
             field_of_view,
+            projection,
             transform: IDENTITY,
             inverse: IDENTITY,
             half_width,
             half_height,
             pixel_size,
+            samples_per_pixel: 1,
+            adaptive_threshold: None,
+            aperture: 0.0,
+            focal_distance: 1.0,
         }
     }
 
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn samples_per_pixel(&self) -> usize {
+        self.samples_per_pixel
+    }
+
+    /// Sets how many rays are averaged into each pixel. With more than one,
+    /// rays are jittered across the pixel to anti-alias edges.
+    pub fn set_samples_per_pixel(&mut self, samples: usize) -> Result<(), ZeroSamplesError> {
+        if samples == 0 {
+            return Err(ZeroSamplesError);
+        }
+        self.samples_per_pixel = samples;
+        Ok(())
+    }
+
+    pub fn adaptive_threshold(&self) -> Option<f64> {
+        self.adaptive_threshold
+    }
+
+    /// Refines only pixels that differ from a neighbor by more than `threshold`
+    pub fn set_adaptive_threshold(&mut self, threshold: Option<f64>) {
+        self.adaptive_threshold = threshold;
+    }
+
+    pub fn aperture(&self) -> f64 {
+        self.aperture
+    }
+
+    pub fn focal_distance(&self) -> f64 {
+        self.focal_distance
+    }
+
+    /// Uses a thin lens of radius `aperture` focused at `focal_distance`
+    pub fn set_depth_of_field(
+        &mut self,
+        aperture: f64,
+        focal_distance: f64,
+    ) -> Result<(), InvalidLensError> {
+        if !(aperture >= 0.0 && focal_distance > 0.0) {
+            return Err(InvalidLensError);
+        }
+        self.aperture = aperture;
+        self.focal_distance = focal_distance;
+        Ok(())
+    }
+
     pub fn ray_for_pixel(&self, x: usize, y: usize) -> Result<Ray, PixelOutOfBoundsError> {
         if x > self.hsize || y > self.vsize {
             return Err(PixelOutOfBoundsError);
         }
 
-        let xoffset = (x as f64 + 0.5) * self.pixel_size;
-        let yoffset = (y as f64 + 0.5) * self.pixel_size;
+        Ok(self.ray_for_point(x as f64 + 0.5, y as f64 + 0.5))
+    }
+
+    /// The ray through a point in pixels from the top left, via the lens if any
+    pub fn ray_for_point(&self, px: f64, py: f64) -> Ray {
+        let xoffset = px * self.pixel_size;
+        let yoffset = py * self.pixel_size;
 
         let world_x = self.half_width - xoffset;
         let world_y = self.half_height - yoffset;
 
-        let pixel = &self.inverse * Point::new(world_x, world_y, -1.0);
-        let origin = &self.inverse * Point::new(0.0, 0.0, 0.0);
-        let direction = (pixel - origin).normalize();
+        // The ray in camera space, before the lens spreads it out
+        let (origin, direction) = match self.projection {
+            Projection::Perspective => (
+                Point::new(0.0, 0.0, 0.0),
+                Vector::new(world_x, world_y, -1.0),
+            ),
+            Projection::Orthographic => (
+                Point::new(world_x, world_y, 0.0),
+                Vector::new(0.0, 0.0, -1.0),
+            ),
+            Projection::Equirectangular => {
+                let longitude = (px / self.hsize as f64 - 0.5) * 2.0 * PI;
+                let latitude = (0.5 - py / self.vsize as f64) * PI;
+                (
+                    Point::new(0.0, 0.0, 0.0),
+                    Vector::new(
+                        -latitude.cos() * longitude.sin(),
+                        latitude.sin(),
+                        -latitude.cos() * longitude.cos(),
+                    ),
+                )
+            }
+            Projection::Fisheye => {
+                let angle = world_x.hypot(world_y);
+                let direction = if angle > 0.0 {
+                    let scale = angle.sin() / angle;
+                    Vector::new(world_x * scale, world_y * scale, -angle.cos())
+                } else {
+                    Vector::new(0.0, 0.0, -1.0)
+                };
+                (Point::new(0.0, 0.0, 0.0), direction)
+            }
+        };
+
+        let lens = if self.aperture > 0.0 {
+            let (lens_x, lens_y) = self.lens_point(px, py);
+            match self.projection {
+                Projection::Perspective | Projection::Orthographic => {
+                    Vector::new(lens_x, lens_y, 0.0)
+                }
+                Projection::Equirectangular | Projection::Fisheye => {
+                    let (u, v) = perpendicular_basis(direction);
+                    u * lens_x + v * lens_y
+                }
+            }
+        } else {
+            Vector::new(0.0, 0.0, 0.0)
+        };
+        let focus = &self.inverse * (origin + direction * self.focal_distance);
+        let origin = &self.inverse * (origin + lens);
+        let direction = (focus - origin).normalize();
+
+        Ray::new(origin, direction)
+    }
+
+    /// A point spread evenly over the lens disc, in camera space
+    fn lens_point(&self, px: f64, py: f64) -> (f64, f64) {
+        let mut rng = Rng::from_parts(&[px.to_bits(), py.to_bits()]);
+        let radius = self.aperture * rng.next_f64().sqrt();
+        let angle = 2.0 * PI * rng.next_f64();
+        (radius * angle.cos(), radius * angle.sin())
+    }
+
+    /// Jittered offsets within pixel `(x, y)`, one per row and column of an
+    /// `n` by `n` grid
+    pub fn sample_offsets(&self, x: usize, y: usize) -> Vec<(f64, f64)> {
+        let n = self.samples_per_pixel;
+        if n == 1 {
+            return vec![(0.5, 0.5)];
+        }
 
-        Ok(Ray::new(origin, direction))
+        let mut rng = Rng::from_parts(&[x as u64, y as u64]);
+        let mut rows = (0..n).collect::<Vec<_>>();
+        for i in (1..n).rev() {
+            rows.swap(i, rng.next_below(i + 1));
+        }
+        rows.into_iter()
+            .enumerate()
+            .map(|(column, row)| {
+                (
+                    (column as f64 + rng.next_f64()) / n as f64,
+                    (row as f64 + rng.next_f64()) / n as f64,
+                )
+            })
+            .collect()
     }
 
     pub fn set_transform(&mut self, transform: Transform) -> Result<(), NoInverseError> {
@@ -76,33 +288,195 @@ impl Camera {
     }
 
     pub fn render(&self, world: &World) -> Canvas {
-        let mut image = Canvas::new(self.hsize, self.vsize);
+        self.render_with(world, |row| (0..self.vsize).map(row).collect())
+    }
 
-        for y in 0..self.vsize {
-            for x in 0..self.hsize {
-                let ray = self.ray_for_pixel(x, y).expect("pixel out of bounds");
-                let color = world.color_from(&ray, RECURSION_DEPTH);
+    /// Renders rows on `threads` worker threads, each taking the next
+    /// unrendered row as it finishes one. Every pixel is computed
+    /// independently, so the result is the same as `render`.
+    pub fn render_parallel(&self, world: &World, threads: usize) -> Canvas {
+        self.render_with(world, |row| self.parallel_rows(threads, row))
+    }
+
+    /// Renders the image, using `rows` to compute every row of a pass
+    fn render_with<R>(&self, world: &World, rows: R) -> Canvas
+    where
+        R: Fn(&RowRenderer) -> Vec<Vec<Color>>,
+    {
+        let colors = match self.adaptive_threshold {
+            None => rows(&|y| self.render_row(world, y)),
+            Some(threshold) => {
+                let centers = rows(&|y| self.center_row(world, y));
+                rows(&|y| self.refine_row(world, &centers, y, threshold))
+            }
+        };
+
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for (y, row) in colors.into_iter().enumerate() {
+            for (x, color) in row.into_iter().enumerate() {
                 image.write_pixel(x, y, color).expect("pixel out of bounds");
             }
         }
-
         image
     }
+
+    fn parallel_rows(&self, threads: usize, row: &RowRenderer) -> Vec<Vec<Color>> {
+        let next_row = AtomicUsize::new(0);
+
+        let mut rows = thread::scope(|scope| {
+            let workers = (0..threads.max(1))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut rows = vec![];
+                        loop {
+                            let y = next_row.fetch_add(1, Ordering::Relaxed);
+                            if y >= self.vsize {
+                                break rows;
+                            }
+                            rows.push((y, row(y)));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("render thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        rows.sort_by_key(|(y, _)| *y);
+        rows.into_iter().map(|(_, row)| row).collect()
+    }
+
+    fn render_row(&self, world: &World, y: usize) -> Vec<Color> {
+        (0..self.hsize)
+            .map(|x| self.color_at_pixel(world, x, y))
+            .collect()
+    }
+
+    fn color_at_pixel(&self, world: &World, x: usize, y: usize) -> Color {
+        let offsets = self.sample_offsets(x, y);
+        let count = offsets.len();
+        offsets
+            .into_iter()
+            .map(|(dx, dy)| self.color_at_point(world, x as f64 + dx, y as f64 + dy))
+            .fold(Color::default(), |sum, color| sum + color)
+            * (1.0 / count as f64)
+    }
+
+    fn color_at_point(&self, world: &World, px: f64, py: f64) -> Color {
+        world.color_from(&self.ray_for_point(px, py), RECURSION_DEPTH)
+    }
+
+    fn center_row(&self, world: &World, y: usize) -> Vec<Color> {
+        (0..self.hsize)
+            .map(|x| self.color_at_point(world, x as f64 + 0.5, y as f64 + 0.5))
+            .collect()
+    }
+
+    /// Keeps the center color of pixels that match all their neighbors, and
+    /// subdivides the rest
+    fn refine_row(
+        &self,
+        world: &World,
+        centers: &[Vec<Color>],
+        y: usize,
+        threshold: f64,
+    ) -> Vec<Color> {
+        (0..self.hsize)
+            .map(|x| {
+                let center = centers[y][x];
+                let neighbors = [
+                    (x.wrapping_sub(1), y),
+                    (x + 1, y),
+                    (x, y.wrapping_sub(1)),
+                    (x, y + 1),
+                ];
+                let contrasted = neighbors.into_iter().any(|(nx, ny)| {
+                    centers
+                        .get(ny)
+                        .and_then(|row| row.get(nx))
+                        .is_some_and(|neighbor| neighbor.difference(&center) > threshold)
+                });
+                if contrasted {
+                    self.subdivide(world, x as f64, y as f64, 1.0, threshold, ADAPTIVE_DEPTH)
+                } else {
+                    center
+                }
+            })
+            .collect()
+    }
+
+    /// Averages the corners of the square of side `size` at `(px, py)`, or of
+    /// its quadrants when the corners differ and `depth` allows
+    fn subdivide(
+        &self,
+        world: &World,
+        px: f64,
+        py: f64,
+        size: f64,
+        threshold: f64,
+        depth: usize,
+    ) -> Color {
+        let corners = [(0.0, 0.0), (size, 0.0), (0.0, size), (size, size)]
+            .map(|(dx, dy)| self.color_at_point(world, px + dx, py + dy));
+        let differs = corners
+            .iter()
+            .any(|corner| corner.difference(&corners[0]) > threshold);
+
+        if depth == 0 || !differs {
+            return corners
+                .into_iter()
+                .fold(Color::default(), |sum, color| sum + color)
+                * 0.25;
+        }
+
+        let half = size / 2.0;
+        [(0.0, 0.0), (half, 0.0), (0.0, half), (half, half)]
+            .into_iter()
+            .map(|(dx, dy)| self.subdivide(world, px + dx, py + dy, half, threshold, depth - 1))
+            .fold(Color::default(), |sum, color| sum + color)
+            * 0.25
+    }
 }
 
+/// Two unit vectors perpendicular to `direction` and to each other
+fn perpendicular_basis(direction: Vector) -> (Vector, Vector) {
+    let up = if direction.y.abs() < 0.9 {
+        Vector::new(0.0, 1.0, 0.0)
+    } else {
+        Vector::new(1.0, 0.0, 0.0)
+    };
+    let u = Vector::cross(up, direction).normalize();
+    let v = Vector::cross(direction, u);
+    (u, v)
+}
+
+/// Computes the colors of one row of the image
+type RowRenderer<'a> = dyn Fn(usize) -> Vec<Color> + Sync + 'a;
+
 #[cfg(test)]
 mod test {
     use std::f64::consts::PI;
 
     use crate::{
-        canvas::Color,
-        transformations::{view_transform, Builder},
+        transformations::{translation, view_transform, Builder},
         world::default_world,
         Point, Vector, EQUALITY_EPSILON,
     };
 
     use super::*;
 
+    /// The default world, seen by an 11x11 camera looking at its center
+    fn test_scene() -> (World, Camera) {
+        let mut c = Camera::new(11, 11, PI / 2.0);
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        c.set_transform(view_transform(from, to, up)).unwrap();
+        (default_world(), c)
+    }
+
     #[test]
     fn construct_camera() {
         let hsize = 160;
@@ -163,16 +537,320 @@ mod test {
 
     #[test]
     fn render() {
+        let (w, c) = test_scene();
+        let image = c.render(&w);
+        assert_eq!(
+            image.pixel_at(5, 5),
+            Ok(Color::new(0.38066, 0.47583, 0.2855))
+        );
+    }
+
+    #[test]
+    fn render_parallel_matches_render() {
+        let (w, c) = test_scene();
+        let expected = c.render(&w);
+        for threads in [1, 3, 32] {
+            assert_eq!(c.render_parallel(&w, threads), expected);
+        }
+    }
+
+    #[test]
+    fn world_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<World>();
+    }
+
+    #[test]
+    fn default_samples_per_pixel() {
+        let mut c = Camera::new(160, 120, PI / 2.0);
+        assert_eq!(c.samples_per_pixel(), 1);
+        assert_eq!(c.sample_offsets(3, 4), vec![(0.5, 0.5)]);
+        assert_eq!(c.set_samples_per_pixel(0), Err(ZeroSamplesError));
+    }
+
+    #[test]
+    fn sample_offsets_are_stratified() {
+        let mut c = Camera::new(160, 120, PI / 2.0);
+        c.set_samples_per_pixel(5).unwrap();
+        let offsets = c.sample_offsets(3, 4);
+        assert_eq!(offsets.len(), 5);
+        let mut columns = offsets
+            .iter()
+            .map(|(dx, _)| (dx * 5.0) as usize)
+            .collect::<Vec<_>>();
+        let mut rows = offsets
+            .iter()
+            .map(|(_, dy)| (dy * 5.0) as usize)
+            .collect::<Vec<_>>();
+        columns.sort();
+        rows.sort();
+        assert_eq!(columns, vec![0, 1, 2, 3, 4]);
+        assert_eq!(rows, vec![0, 1, 2, 3, 4]);
+        assert_eq!(c.sample_offsets(3, 4), offsets);
+        assert_ne!(c.sample_offsets(4, 3), offsets);
+    }
+
+    #[test]
+    fn supersampling_blends_edges() {
+        let (w, mut c) = test_scene();
+        let single = c.render(&w);
+        c.set_samples_per_pixel(16).unwrap();
+        let image = c.render(&w);
+
+        // The corner misses everything however it is sampled
+        assert_eq!(image.pixel_at(0, 0), Ok(Color::new(0.0, 0.0, 0.0)));
+        // Pixels on the silhouette of the sphere are partially covered
+        let edge = (0..11)
+            .map(|x| {
+                (
+                    image.pixel_at(x, 5).unwrap(),
+                    single.pixel_at(x, 5).unwrap(),
+                )
+            })
+            .any(|(sampled, centered)| sampled != centered);
+        assert!(edge);
+        assert_eq!(c.render_parallel(&w, 4), image);
+    }
+
+    #[test]
+    fn adaptive_sampling_refines_only_contrasted_pixels() {
+        let (w, mut c) = test_scene();
+        let single = c.render(&w);
+        assert_eq!(c.adaptive_threshold(), None);
+        c.set_adaptive_threshold(Some(0.1));
+        let image = c.render(&w);
+
+        // Pixels that match their neighbors keep their single sample, while
+        // some on the silhouette of the sphere are refined
+        let pixel = |x: usize, y: usize| single.pixel_at(x, y).unwrap();
+        let mut refined = 0;
+        for y in 1..10 {
+            for x in 1..10 {
+                let flat = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .into_iter()
+                    .all(|(nx, ny)| pixel(nx, ny).difference(&pixel(x, y)) <= 0.1);
+                if flat {
+                    assert_eq!(image.pixel_at(x, y), single.pixel_at(x, y));
+                } else if image.pixel_at(x, y) != single.pixel_at(x, y) {
+                    refined += 1;
+                }
+            }
+        }
+        assert!(refined > 0);
+        assert_eq!(c.render_parallel(&w, 3), image);
+    }
+
+    #[test]
+    fn pinhole_by_default() {
+        let mut c = Camera::new(201, 101, PI / 2.0);
+        assert_eq!(c.aperture(), 0.0);
+        assert_eq!(c.focal_distance(), 1.0);
+        let pinhole = c.ray_for_pixel(0, 0).unwrap();
+        c.set_depth_of_field(0.0, 5.0).unwrap();
+        let r = c.ray_for_pixel(0, 0).unwrap();
+        assert_eq!(r.origin, pinhole.origin);
+        assert_eq!(r.direction, pinhole.direction);
+        assert_eq!(c.set_depth_of_field(-1.0, 5.0), Err(InvalidLensError));
+        assert_eq!(c.set_depth_of_field(0.5, 0.0), Err(InvalidLensError));
+    }
+
+    #[test]
+    fn lens_rays_meet_on_focal_plane() {
+        let mut c = Camera::new(201, 101, PI / 2.0);
+        c.set_transform(translation(0.0, 0.0, 3.0)).unwrap();
+        let pinhole = Camera::new(201, 101, PI / 2.0);
+        c.set_depth_of_field(0.25, 4.0).unwrap();
+
+        for (x, y) in [(20, 30), (100, 50), (190, 7)] {
+            let r = c.ray_for_pixel(x, y).unwrap();
+            let on_lens = r.origin - Point::new(0.0, 0.0, -3.0);
+            assert!(on_lens.z.abs() < EQUALITY_EPSILON);
+            assert!(on_lens.magnitude() <= 0.25);
+
+            // Both rays cross the focal plane, 4 units in front of the
+            // camera, at the same point
+            let p = pinhole.ray_for_pixel(x, y).unwrap();
+            let focus = p.position(4.0 / -p.direction.z) + Vector::new(0.0, 0.0, -3.0);
+            assert_eq!(r.position(4.0 / -r.direction.z), focus);
+        }
+    }
+
+    #[test]
+    fn focal_plane_stays_sharp() {
+        let (w, mut c) = test_scene();
+        c.set_samples_per_pixel(16).unwrap();
+        let sharp = c.render(&w);
+
+        // Focused on the front of the sphere, its silhouette barely moves,
+        // but focused far behind it the edges smear into the background
+        c.set_depth_of_field(0.05, 4.0).unwrap();
+        let focused = c.render(&w);
+        c.set_depth_of_field(0.5, 50.0).unwrap();
+        let blurred = c.render(&w);
+        let error = |image: &Canvas| {
+            (0..11)
+                .flat_map(|y| (0..11).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    image
+                        .pixel_at(x, y)
+                        .unwrap()
+                        .difference(&sharp.pixel_at(x, y).unwrap())
+                })
+                .sum::<f64>()
+        };
+        assert!(error(&focused) < error(&blurred));
+    }
+
+    #[test]
+    fn orthographic_pixel_size() {
+        let c = Camera::new_orthographic(200, 125, 10.0);
+        assert_eq!(c.projection(), Projection::Orthographic);
+        assert!((c.pixel_size - 0.05).abs() < EQUALITY_EPSILON);
+        let c = Camera::new_orthographic(125, 200, 10.0);
+        assert!((c.pixel_size - 0.05).abs() < EQUALITY_EPSILON);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let c = Camera::new_orthographic(201, 101, 20.1);
+        let r = c.ray_for_pixel(100, 50).unwrap();
+        assert_eq!(r.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(r.direction, Vector::new(0.0, 0.0, -1.0));
+        let r = c.ray_for_pixel(0, 0).unwrap();
+        assert_eq!(r.origin, Point::new(10.0, 5.0, 0.0));
+        assert_eq!(r.direction, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn orthographic_ray_after_transform() {
+        let mut c = Camera::new_orthographic(201, 101, 20.1);
+        c.set_transform(
+            Builder::new()
+                .translation(0.0, -2.0, 5.0)
+                .rotation_y(PI / 4.0)
+                .transform(),
+        )
+        .unwrap();
+        let r = c.ray_for_pixel(100, 0).unwrap();
+        assert_eq!(r.origin, Point::new(0.0, 7.0, -5.0));
+        assert_eq!(
+            r.direction,
+            Vector::new(2_f64.sqrt() / 2.0, 0.0, -(2_f64.sqrt()) / 2.0)
+        );
+    }
+
+    #[test]
+    fn render_orthographic() {
         let w = default_world();
-        let mut c = Camera::new(11, 11, PI / 2.0);
+        let mut c = Camera::new_orthographic(11, 11, 2.2);
         let from = Point::new(0.0, 0.0, -5.0);
         let to = Point::new(0.0, 0.0, 0.0);
         let up = Vector::new(0.0, 1.0, 0.0);
         c.set_transform(view_transform(from, to, up)).unwrap();
         let image = c.render(&w);
+
+        // The center ray is the same as for a perspective camera, and the
+        // unit sphere fills the view out to its edges
         assert_eq!(
             image.pixel_at(5, 5),
             Ok(Color::new(0.38066, 0.47583, 0.2855))
         );
+        assert_ne!(image.pixel_at(1, 5), Ok(Color::new(0.0, 0.0, 0.0)));
+        assert_eq!(image.pixel_at(0, 0), Ok(Color::new(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn equirectangular_directions() {
+        let c = Camera::new_equirectangular(360, 180);
+        assert_eq!(c.projection(), Projection::Equirectangular);
+        let cases = [
+            (180.0, 90.0, Vector::new(0.0, 0.0, -1.0)),
+            (90.0, 90.0, Vector::new(1.0, 0.0, 0.0)),
+            (270.0, 90.0, Vector::new(-1.0, 0.0, 0.0)),
+            (0.0, 90.0, Vector::new(0.0, 0.0, 1.0)),
+            (180.0, 0.0, Vector::new(0.0, 1.0, 0.0)),
+            (180.0, 180.0, Vector::new(0.0, -1.0, 0.0)),
+            (
+                180.0,
+                45.0,
+                Vector::new(0.0, 2_f64.sqrt() / 2.0, -(2_f64.sqrt()) / 2.0),
+            ),
+        ];
+        for (px, py, direction) in cases {
+            let r = c.ray_for_point(px, py);
+            assert_eq!(r.origin, Point::new(0.0, 0.0, 0.0));
+            assert_eq!(r.direction, direction);
+        }
+    }
+
+    #[test]
+    fn equirectangular_ray_after_transform() {
+        let mut c = Camera::new_equirectangular(360, 180);
+        c.set_transform(
+            Builder::new()
+                .translation(0.0, -2.0, 5.0)
+                .rotation_y(PI / 4.0)
+                .transform(),
+        )
+        .unwrap();
+        let r = c.ray_for_point(180.0, 90.0);
+        assert_eq!(r.origin, Point::new(0.0, 2.0, -5.0));
+        assert_eq!(
+            r.direction,
+            Vector::new(2_f64.sqrt() / 2.0, 0.0, -(2_f64.sqrt()) / 2.0)
+        );
+    }
+
+    #[test]
+    fn fisheye_directions() {
+        let c = Camera::new_fisheye(200, 100, PI);
+        assert_eq!(c.projection(), Projection::Fisheye);
+        assert_eq!(c.field_of_view, PI);
+        let cases = [
+            (100.0, 50.0, Vector::new(0.0, 0.0, -1.0)),
+            (0.0, 50.0, Vector::new(1.0, 0.0, 0.0)),
+            (200.0, 50.0, Vector::new(-1.0, 0.0, 0.0)),
+            (
+                100.0,
+                0.0,
+                Vector::new(0.0, 2_f64.sqrt() / 2.0, -(2_f64.sqrt()) / 2.0),
+            ),
+            (
+                50.0,
+                50.0,
+                Vector::new(2_f64.sqrt() / 2.0, 0.0, -(2_f64.sqrt()) / 2.0),
+            ),
+        ];
+        for (px, py, direction) in cases {
+            let r = c.ray_for_point(px, py);
+            assert_eq!(r.origin, Point::new(0.0, 0.0, 0.0));
+            assert_eq!(r.direction, direction);
+        }
+    }
+
+    #[test]
+    fn fisheye_beyond_half_turn() {
+        let c = Camera::new_fisheye(100, 100, 1.5 * PI);
+        let r = c.ray_for_point(0.0, 50.0);
+        assert_eq!(
+            r.direction,
+            Vector::new(2_f64.sqrt() / 2.0, 0.0, 2_f64.sqrt() / 2.0)
+        );
+    }
+
+    #[test]
+    fn panoramic_lens_faces_along_ray() {
+        let mut c = Camera::new_equirectangular(360, 180);
+        c.set_depth_of_field(0.5, 3.0).unwrap();
+        for (px, py) in [(90.0, 90.0), (180.0, 0.5), (33.0, 140.0)] {
+            let sharp = Camera::new_equirectangular(360, 180).ray_for_point(px, py);
+            let r = c.ray_for_point(px, py);
+            let lens = r.origin - Point::new(0.0, 0.0, 0.0);
+            assert!(lens.magnitude() <= 0.5);
+            assert!(Vector::dot(lens, sharp.direction).abs() < EQUALITY_EPSILON);
+            let focus = sharp.position(3.0);
+            let to_focus = focus - r.origin;
+            assert_eq!(r.direction, to_focus.normalize());
+        }
     }
 }
@@ -14,6 +14,14 @@ impl Color {
     pub const fn new(red: f64, green: f64, blue: f64) -> Self {
         Color { red, green, blue }
     }
+
+    /// The largest difference between any channel of the two colors
+    pub fn difference(&self, other: &Color) -> f64 {
+        (self.red - other.red)
+            .abs()
+            .max((self.green - other.green).abs())
+            .max((self.blue - other.blue).abs())
+    }
 }
 
 impl PartialEq for Color {
@@ -203,6 +211,14 @@ mod tests {
         assert_eq!(2.0 * c, Color::new(0.4, 0.6, 0.8));
     }
 
+    #[test]
+    fn difference_between_colors() {
+        let c1 = Color::new(0.9, 0.6, 0.75);
+        let c2 = Color::new(0.7, 0.1, 0.8);
+        assert!((c1.difference(&c2) - 0.5).abs() < EQUALITY_EPSILON);
+        assert_eq!(c1.difference(&c1), 0.0);
+    }
+
     #[test]
     fn multiply_colors() {
         let c1 = Color::new(1.0, 0.2, 0.4);
@@ -1,6 +1,8 @@
-use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
-
-use crate::{rays::Ray, shapes::Shape, Point, Vector, EQUALITY_EPSILON};
+use crate::{
+    rays::{Ray, RayKind},
+    shapes::Shape,
+    Point, Vector, EQUALITY_EPSILON,
+};
 
 const SHADOW_EPSILON: f64 = 0.00001;
 
@@ -8,11 +10,19 @@ const SHADOW_EPSILON: f64 = 0.00001;
 pub struct Intersection<'object> {
     pub t: f64,
     pub object: &'object Shape,
+    /// Surface coordinates of the hit, for shapes that interpolate across
+    /// their surface (e.g. `SmoothTriangle`)
+    pub u: f64,
+    pub v: f64,
 }
 
 impl<'object> Intersection<'object> {
     pub fn new(t: f64, object: &'object Shape) -> Self {
-        Intersection { t, object }
+        Intersection::with_uv(t, object, 0.0, 0.0)
+    }
+
+    pub fn with_uv(t: f64, object: &'object Shape, u: f64, v: f64) -> Self {
+        Intersection { t, object, u, v }
     }
 }
 
@@ -72,9 +82,12 @@ pub struct HitInfo<'object> {
     pub normal: Vector,
     pub inside: bool,
     pub over_point: Point,
+    pub under_point: Point,
     pub reflectv: Vector,
     pub n1: f64,
     pub n2: f64,
+    /// Kind of the ray that made the hit, which refracted rays carry on
+    pub ray_kind: RayKind,
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
@@ -91,32 +104,40 @@ impl<'object> HitInfo<'object> {
         let object = intersection.object;
         let point = ray.position(t);
         let eyev = -ray.direction;
-        let naive_normal = object.normal_at(point);
+        let naive_normal = object.normal_at_hit(point, intersection);
         let inside = Vector::dot(naive_normal, eyev) < 0.0;
         let normal = if inside { -naive_normal } else { naive_normal };
         let over_point = point + normal * SHADOW_EPSILON;
+        let under_point = point - normal * SHADOW_EPSILON;
         let reflectv = ray.direction.reflect(normal);
 
         let mut n1 = 1.0;
         let mut n2 = 1.0;
         // Assumes intersections is sorted by t value of the intersection
-        let mut containers = BTreeMap::<usize, &Shape>::new();
+        let mut containers = Vec::<&Shape>::new();
         for (index, intersection) in intersections.iter().enumerate() {
             if index == hit_index {
-                if let Some((_, object)) = containers.last_key_value() {
+                if let Some(object) = containers.last() {
                     n1 = object.material.refractive_index;
                 }
             }
 
-            // TODO: test _object references_ for equality
-            if containers.remove(&index).is_none() {
-                containers.insert(index, intersection.object);
+            // Containers are tracked by object identity, since distinct shapes
+            // may compare equal
+            if let Some(position) = containers
+                .iter()
+                .position(|object| std::ptr::eq(*object, intersection.object))
+            {
+                containers.remove(position);
+            } else {
+                containers.push(intersection.object);
             }
 
             if index == hit_index {
-                if let Some((_, object)) = containers.last_key_value() {
+                if let Some(object) = containers.last() {
                     n2 = object.material.refractive_index;
                 }
+                break;
             }
         }
         Some(HitInfo {
@@ -127,18 +148,38 @@ impl<'object> HitInfo<'object> {
             normal,
             inside,
             over_point,
+            under_point,
             reflectv,
             n1,
             n2,
+            ray_kind: ray.kind,
         })
     }
+
+    /// Schlick's approximation of the Fresnel reflectance, i.e. the fraction
+    /// of light reflected rather than refracted at the hit.
+    pub fn schlick(&self) -> f64 {
+        let mut cos = Vector::dot(self.eyev, self.normal);
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n * n * (1.0 - cos * cos);
+            if sin2_t > 1.0 {
+                // Total internal reflection
+                return 1.0;
+            }
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
         rays::Ray,
-        shapes::{Plane, Sphere},
+        shapes::{Cube, Plane, Sphere},
         transformations::{scaling, translation},
         Point, Vector,
     };
@@ -153,6 +194,14 @@ mod test {
         assert_eq!(i.object, &s);
     }
 
+    #[test]
+    fn intersection_with_uv() {
+        let s = Shape::new(Sphere);
+        let i = Intersection::with_uv(3.5, &s, 0.2, 0.4);
+        assert_eq!(i.u, 0.2);
+        assert_eq!(i.v, 0.4);
+    }
+
     #[test]
     fn aggregate_intersections() {
         let s = Shape::new(Sphere);
@@ -255,6 +304,18 @@ mod test {
         assert!(hit_info.point.z > hit_info.over_point.z);
     }
 
+    #[test]
+    fn hit_should_offset_under_point() {
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut shape = Sphere::new_glass();
+        shape.set_transform(translation(0.0, 0.0, 1.0)).unwrap();
+        let i = Intersection::new(5.0, &shape);
+        let xs = Intersections::new(vec![i.clone()]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        assert!(hit_info.under_point.z > SHADOW_EPSILON / 2.0);
+        assert!(hit_info.point.z < hit_info.under_point.z);
+    }
+
     #[test]
     fn precompute_reflection_vector() {
         let shape = Shape::new(Plane);
@@ -293,7 +354,7 @@ mod test {
             Intersection::new(6.0, &a),
         ]);
 
-        let examples = vec![
+        let examples = [
             (1.0, 1.5),
             (1.5, 2.0),
             (2.0, 2.5),
@@ -307,4 +368,113 @@ mod test {
             assert_eq!(hit_info.n2, pair.1);
         }
     }
+
+    #[test]
+    fn schlick_small_angle_and_n2_greater() {
+        let shape = Sphere::new_glass();
+        let r = Ray::new(Point::new(0.0, 0.99, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![Intersection::new(1.8589, &shape)]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let reflectance = hit_info.schlick();
+        assert!((reflectance - 0.48873).abs() < EQUALITY_EPSILON);
+    }
+
+    #[test]
+    fn n1_and_n2_overlapping_objects() {
+        let mut a = Sphere::new_glass();
+        a.set_transform(translation(0.0, 0.0, -0.5)).unwrap();
+        a.material.refractive_index = 1.5;
+        let mut b = Sphere::new_glass();
+        b.set_transform(translation(0.0, 0.0, 0.5)).unwrap();
+        b.material.refractive_index = 2.0;
+
+        let r = Ray::new(Point::new(0.0, 0.0, -4.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(2.5, &a),
+            Intersection::new(3.5, &b),
+            Intersection::new(4.5, &a),
+            Intersection::new(5.5, &b),
+        ]);
+
+        let examples = [(1.0, 1.5), (1.5, 2.0), (2.0, 2.0), (2.0, 1.0)];
+        for (index, pair) in examples.iter().enumerate() {
+            let hit_info = HitInfo::prepare(&xs, &r, index).unwrap();
+            assert_eq!(hit_info.n1, pair.0);
+            assert_eq!(hit_info.n2, pair.1);
+        }
+    }
+
+    #[test]
+    fn n1_and_n2_equal_but_distinct_objects() {
+        let a = Sphere::new_glass();
+        let b = Sphere::new_glass();
+        assert_eq!(a, b);
+
+        let r = Ray::new(Point::new(0.0, 0.0, -4.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(3.0, &a),
+            Intersection::new(3.0, &b),
+            Intersection::new(5.0, &a),
+            Intersection::new(5.0, &b),
+        ]);
+
+        let hit_info = HitInfo::prepare(&xs, &r, 2).unwrap();
+        assert_eq!(hit_info.n1, 1.5);
+        assert_eq!(hit_info.n2, 1.5);
+        let hit_info = HitInfo::prepare(&xs, &r, 3).unwrap();
+        assert_eq!(hit_info.n1, 1.5);
+        assert_eq!(hit_info.n2, 1.0);
+    }
+
+    #[test]
+    fn schlick_under_total_internal_reflection() {
+        let shape = Sphere::new_glass();
+        let r = Ray::new(
+            Point::new(0.0, 0.0, 2_f64.sqrt() / 2.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let xs = Intersections::new(vec![
+            Intersection::new(-(2_f64.sqrt()) / 2.0, &shape),
+            Intersection::new(2_f64.sqrt() / 2.0, &shape),
+        ]);
+        let hit_info = HitInfo::prepare(&xs, &r, 1).unwrap();
+        assert_eq!(hit_info.schlick(), 1.0);
+    }
+
+    #[test]
+    fn schlick_perpendicular_viewing_angle() {
+        let shape = Sphere::new_glass();
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(-1.0, &shape),
+            Intersection::new(1.0, &shape),
+        ]);
+        let hit_info = HitInfo::prepare(&xs, &r, 1).unwrap();
+        assert!((hit_info.schlick() - 0.04).abs() < EQUALITY_EPSILON);
+    }
+
+    #[test]
+    fn n1_and_n2_sphere_inside_cube() {
+        let mut cube = Shape::new(Cube);
+        cube.set_transform(scaling(3.0, 3.0, 3.0)).unwrap();
+        cube.material.transparaency = 1.0;
+        cube.material.refractive_index = 1.33;
+        let sphere = Sphere::new_glass();
+
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(
+            cube.intersect(&r)
+                .into_iter()
+                .chain(sphere.intersect(&r))
+                .collect(),
+        );
+        assert_eq!(xs.len(), 4);
+
+        let examples = [(1.0, 1.33), (1.33, 1.5), (1.5, 1.33), (1.33, 1.0)];
+        for (index, pair) in examples.iter().enumerate() {
+            let hit_info = HitInfo::prepare(&xs, &r, index).unwrap();
+            assert_eq!(hit_info.n1, pair.0);
+            assert_eq!(hit_info.n2, pair.1);
+        }
+    }
 }
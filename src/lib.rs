@@ -1,10 +1,14 @@
+pub mod bounds;
+pub mod bvh;
 pub mod camera;
 pub mod canvas;
 pub mod intersections;
 pub mod lights;
 pub mod materials;
 pub mod matrices;
+pub mod obj;
 pub mod patterns;
+mod random;
 pub mod rays;
 pub mod shapes;
 pub mod transformations;
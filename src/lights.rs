@@ -1,9 +1,169 @@
-use crate::{canvas::Color, Point};
+use std::{any::Any, f64::consts::PI, fmt::Debug};
+
+use crate::{canvas::Color, random::Rng, Point, Vector};
+
+/// One part of a light, as seen from a point it illuminates
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LightSample {
+    /// Unit vector from the lit point towards the light
+    pub direction: Vector,
+    /// How far the light is along `direction`, infinite if it has no position
+    pub distance: f64,
+    /// The light arriving at the point
+    pub intensity: Color,
+    /// The share of the light's own intensity that adds to ambient light,
+    /// which lights faded by distance or direction still contribute in full
+    pub ambient: Color,
+}
+
+impl LightSample {
+    /// The sample of a light at `position` seen from `point`
+    pub fn from_position(point: Point, position: Point, intensity: Color) -> Self {
+        let to_light = position - point;
+        LightSample {
+            direction: to_light.normalize(),
+            distance: to_light.magnitude(),
+            intensity,
+            ambient: intensity,
+        }
+    }
+}
+
+pub trait LightModel: Clone + Debug + PartialEq + Send + Sync + 'static {
+    /// The points on the light that illuminate `point`, each carrying an
+    /// equal share of the light
+    fn samples(&self, point: Point) -> Vec<LightSample>;
+}
+
+trait DynamicLightModel: Debug + Send + Sync {
+    fn samples(&self, point: Point) -> Vec<LightSample>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn dynamic_clone(&self) -> Box<dyn DynamicLightModel>;
+
+    fn dynamic_eq(&self, other: &dyn DynamicLightModel) -> bool;
+}
+
+impl<T: LightModel> DynamicLightModel for T {
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        self.samples(point)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dynamic_clone(&self) -> Box<dyn DynamicLightModel> {
+        Box::new(self.clone())
+    }
+
+    fn dynamic_eq(&self, other: &dyn DynamicLightModel) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .is_some_and(|other| self == other)
+    }
+}
+
+#[derive(Debug)]
+pub struct Light {
+    model: Box<dyn DynamicLightModel>,
+}
+
+impl Light {
+    pub fn new(model: impl LightModel) -> Self {
+        Light {
+            model: Box::new(model),
+        }
+    }
+
+    pub fn samples(&self, point: Point) -> Vec<LightSample> {
+        self.model.samples(point)
+    }
+}
+
+impl Clone for Light {
+    fn clone(&self) -> Self {
+        Light {
+            model: self.model.dynamic_clone(),
+        }
+    }
+}
+
+impl PartialEq for Light {
+    fn eq(&self, other: &Self) -> bool {
+        self.model.dynamic_eq(other.model.as_ref())
+    }
+}
+
+/// How a light fades with distance
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub enum Attenuation {
+    /// The light is equally bright at any distance
+    #[default]
+    Constant,
+    /// The light is divided by a polynomial in the distance
+    Polynomial(Polynomial),
+    /// The light falls off with the square of the distance, as real lights
+    /// do, so its intensity is what it gives one unit away
+    InverseSquare,
+}
+
+/// The polynomial `constant + linear * d + quadratic * d²`, whose
+/// coefficients are not negative and not all zero
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Polynomial {
+    constant: f64,
+    linear: f64,
+    quadratic: f64,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidAttenuationError;
+
+impl Attenuation {
+    pub fn polynomial(
+        constant: f64,
+        linear: f64,
+        quadratic: f64,
+    ) -> Result<Self, InvalidAttenuationError> {
+        let coefficients = [constant, linear, quadratic];
+        if !coefficients.iter().all(|c| *c >= 0.0) || !coefficients.iter().any(|c| *c > 0.0) {
+            return Err(InvalidAttenuationError);
+        }
+        Ok(Attenuation::Polynomial(Polynomial {
+            constant,
+            linear,
+            quadratic,
+        }))
+    }
+
+    /// The fraction of the light left `distance` away from it. Where the
+    /// light would be divided by zero, it is left at full strength.
+    pub fn factor(&self, distance: f64) -> f64 {
+        let divisor = match *self {
+            Attenuation::Constant => 1.0,
+            Attenuation::Polynomial(Polynomial {
+                constant,
+                linear,
+                quadratic,
+            }) => constant + linear * distance + quadratic * distance * distance,
+            Attenuation::InverseSquare => distance * distance,
+        };
+        if divisor > 0.0 {
+            1.0 / divisor
+        } else {
+            1.0
+        }
+    }
+}
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct PointLight {
     pub position: Point,
     pub intensity: Color,
+    pub attenuation: Attenuation,
 }
 
 impl PointLight {
@@ -11,12 +171,189 @@ impl PointLight {
         PointLight {
             intensity,
             position,
+            attenuation: Attenuation::Constant,
+        }
+    }
+}
+
+impl LightModel for PointLight {
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        let mut sample = LightSample::from_position(point, self.position, self.intensity);
+        sample.intensity = sample.intensity * self.attenuation.factor(sample.distance);
+        vec![sample]
+    }
+}
+
+/// A rectangular light spanning `u_edge` and `v_edge` from `corner`, divided
+/// into `usteps` by `vsteps` cells. Each cell is sampled once, at a random
+/// point within it when `jitter` is set and at its center otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaLight {
+    pub corner: Point,
+    pub u_edge: Vector,
+    pub usteps: usize,
+    pub v_edge: Vector,
+    pub vsteps: usize,
+    pub intensity: Color,
+    pub jitter: bool,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ZeroStepsError;
+
+impl AreaLight {
+    pub fn new(
+        corner: Point,
+        u_edge: Vector,
+        usteps: usize,
+        v_edge: Vector,
+        vsteps: usize,
+        intensity: Color,
+    ) -> Result<Self, ZeroStepsError> {
+        if usteps == 0 || vsteps == 0 {
+            return Err(ZeroStepsError);
+        }
+        Ok(AreaLight {
+            corner,
+            u_edge,
+            usteps,
+            v_edge,
+            vsteps,
+            intensity,
+            jitter: true,
+        })
+    }
+
+    /// The center of the light
+    pub fn position(&self) -> Point {
+        self.corner + self.u_edge * 0.5 + self.v_edge * 0.5
+    }
+
+    /// The point `(du, dv)` of the way across cell `(u, v)`
+    pub fn point_on_light(&self, u: usize, v: usize, du: f64, dv: f64) -> Point {
+        self.corner
+            + self.u_edge * ((u as f64 + du) / self.usteps as f64)
+            + self.v_edge * ((v as f64 + dv) / self.vsteps as f64)
+    }
+}
+
+impl LightModel for AreaLight {
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        let mut rng = Rng::from_parts(&[point.x.to_bits(), point.y.to_bits(), point.z.to_bits()]);
+        let mut offset = || if self.jitter { rng.next_f64() } else { 0.5 };
+
+        let mut samples = Vec::with_capacity(self.usteps * self.vsteps);
+        for v in 0..self.vsteps {
+            for u in 0..self.usteps {
+                let (du, dv) = (offset(), offset());
+                samples.push(LightSample::from_position(
+                    point,
+                    self.point_on_light(u, v, du, dv),
+                    self.intensity,
+                ));
+            }
+        }
+        samples
+    }
+}
+
+/// A light at `position` shining along `direction`. Points within
+/// `inner_angle` of that axis get the full intensity, which fades smoothly to
+/// nothing at `outer_angle`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Color,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidConeError;
+
+impl SpotLight {
+    pub fn new(
+        position: Point,
+        direction: Vector,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Color,
+    ) -> Result<Self, InvalidConeError> {
+        let valid_angles = 0.0 <= inner_angle && inner_angle <= outer_angle && outer_angle <= PI;
+        if !(valid_angles && direction.magnitude() > 0.0) {
+            return Err(InvalidConeError);
+        }
+        Ok(SpotLight {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+        })
+    }
+
+    /// How much of the light reaches `point`, from 1 inside the inner cone
+    /// to 0 outside the outer one
+    pub fn falloff(&self, point: Point) -> f64 {
+        if point == self.position {
+            return 1.0;
+        }
+        let cos_angle = Vector::dot((point - self.position).normalize(), self.direction);
+        let cos_inner = self.inner_angle.cos();
+        let cos_outer = self.outer_angle.cos();
+        if cos_angle >= cos_inner {
+            1.0
+        } else if cos_angle <= cos_outer {
+            0.0
+        } else {
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
         }
     }
 }
 
+impl LightModel for SpotLight {
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        vec![LightSample {
+            intensity: self.intensity * self.falloff(point),
+            ..LightSample::from_position(point, self.position, self.intensity)
+        }]
+    }
+}
+
+/// A light infinitely far away, like the sun, whose rays all travel along
+/// `direction`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: Vector,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, intensity: Color) -> Self {
+        DirectionalLight {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+impl LightModel for DirectionalLight {
+    fn samples(&self, _point: Point) -> Vec<LightSample> {
+        vec![LightSample {
+            direction: -self.direction,
+            distance: f64::INFINITY,
+            intensity: self.intensity,
+            ambient: self.intensity,
+        }]
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use crate::EQUALITY_EPSILON;
+
     use super::*;
 
     #[test]
@@ -26,5 +363,309 @@ mod test {
         let light = PointLight::new(position, intensity);
         assert_eq!(light.position, position);
         assert_eq!(light.intensity, intensity);
+        assert_eq!(light.attenuation, Attenuation::Constant);
+    }
+
+    #[test]
+    fn attenuation_factors() {
+        let polynomial = Attenuation::polynomial(1.0, 0.5, 0.25).unwrap();
+        let cases = [
+            (Attenuation::Constant, 0.0, 1.0),
+            (Attenuation::Constant, 1000.0, 1.0),
+            (polynomial, 0.0, 1.0),
+            (polynomial, 2.0, 1.0 / 3.0),
+            (polynomial, 4.0, 1.0 / 7.0),
+            (Attenuation::InverseSquare, 1.0, 1.0),
+            (Attenuation::InverseSquare, 2.0, 0.25),
+            (Attenuation::InverseSquare, 10.0, 0.01),
+        ];
+        for (attenuation, distance, expected) in cases {
+            assert!((attenuation.factor(distance) - expected).abs() < EQUALITY_EPSILON);
+        }
+    }
+
+    #[test]
+    fn attenuation_at_zero_distance() {
+        let linear = Attenuation::polynomial(0.0, 1.0, 0.0).unwrap();
+        assert_eq!(linear.factor(0.0), 1.0);
+        assert_eq!(Attenuation::InverseSquare.factor(0.0), 1.0);
+    }
+
+    #[test]
+    fn polynomial_attenuation_rejects_invalid_coefficients() {
+        assert_eq!(
+            Attenuation::polynomial(0.0, 0.0, 0.0),
+            Err(InvalidAttenuationError)
+        );
+        assert_eq!(
+            Attenuation::polynomial(1.0, -0.5, 0.0),
+            Err(InvalidAttenuationError)
+        );
+        assert_eq!(
+            Attenuation::polynomial(f64::NAN, 1.0, 0.0),
+            Err(InvalidAttenuationError)
+        );
+        let quadratic = Attenuation::polynomial(0.0, 0.0, 1.0).unwrap();
+        assert_eq!(quadratic.factor(2.0), 0.25);
+    }
+
+    #[test]
+    fn attenuated_point_light_dims_with_distance() {
+        let mut light = PointLight::new(Point::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        light.attenuation = Attenuation::InverseSquare;
+        let near = LightModel::samples(&light, Point::new(0.0, 0.0, 2.0));
+        let far = LightModel::samples(&light, Point::new(0.0, 0.0, 1000.0));
+        assert_eq!(near[0].intensity, Color::new(0.25, 0.25, 0.25));
+        assert_eq!(far[0].intensity, Color::new(0.0, 0.0, 0.0));
+        assert_eq!(far[0].ambient, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(far[0].distance, 1000.0);
+    }
+
+    #[test]
+    fn point_light_has_one_sample() {
+        let light = Light::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
+        let samples = light.samples(Point::new(1.0, 2.0, 3.0));
+        assert_eq!(samples.len(), 1);
+        let to_light = Point::new(0.0, 0.0, -10.0) - Point::new(1.0, 2.0, 3.0);
+        assert_eq!(samples[0].direction, to_light.normalize());
+        assert_eq!(samples[0].distance, to_light.magnitude());
+        assert_eq!(samples[0].intensity, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn create_area_light() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = AreaLight::new(corner, v1, 4, v2, 2, Color::new(1.0, 1.0, 1.0)).unwrap();
+        assert_eq!(light.corner, corner);
+        assert_eq!(light.usteps, 4);
+        assert_eq!(light.vsteps, 2);
+        assert!(light.jitter);
+        assert_eq!(light.position(), Point::new(1.0, 0.0, 0.5));
+        assert_eq!(
+            LightModel::samples(&light, Point::new(0.0, 5.0, 0.0)).len(),
+            8
+        );
+    }
+
+    #[test]
+    fn find_single_point_on_area_light() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = AreaLight::new(corner, v1, 4, v2, 2, Color::new(1.0, 1.0, 1.0)).unwrap();
+        let cases = [
+            (0, 0, Point::new(0.25, 0.0, 0.25)),
+            (1, 0, Point::new(0.75, 0.0, 0.25)),
+            (0, 1, Point::new(0.25, 0.0, 0.75)),
+            (2, 0, Point::new(1.25, 0.0, 0.25)),
+            (3, 1, Point::new(1.75, 0.0, 0.75)),
+        ];
+        for (u, v, expected) in cases {
+            assert_eq!(light.point_on_light(u, v, 0.5, 0.5), expected);
+        }
+    }
+
+    #[test]
+    fn jittered_samples_stay_in_their_cells() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = AreaLight::new(corner, v1, 4, v2, 2, Color::new(1.0, 1.0, 1.0)).unwrap();
+        let point = Point::new(0.0, 5.0, 0.0);
+        let samples = LightModel::samples(&light, point);
+        for (index, sample) in samples.iter().enumerate() {
+            let (u, v) = ((index % 4) as f64, (index / 4) as f64);
+            let p = point + sample.direction * sample.distance;
+            assert!(p.x >= u * 0.5 && p.x <= (u + 1.0) * 0.5);
+            assert!(p.z >= v * 0.5 && p.z <= (v + 1.0) * 0.5);
+            assert_eq!(p.y, 0.0);
+        }
+        assert_eq!(LightModel::samples(&light, point), samples);
+        assert_ne!(
+            LightModel::samples(&light, Point::new(0.0, 6.0, 0.0)),
+            samples
+        );
+    }
+
+    #[test]
+    fn area_light_without_jitter_samples_cell_centers() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let mut light = AreaLight::new(corner, v1, 4, v2, 2, Color::new(1.0, 1.0, 1.0)).unwrap();
+        light.jitter = false;
+        let point = Point::new(0.0, 5.0, 0.0);
+        let samples = LightModel::samples(&light, point);
+        let corner = Point::new(0.25, 0.0, 0.25);
+        let last = Point::new(1.75, 0.0, 0.75);
+        assert_eq!(
+            samples[0],
+            LightSample::from_position(point, corner, light.intensity)
+        );
+        assert_eq!(
+            samples[7],
+            LightSample::from_position(point, last, light.intensity)
+        );
+    }
+
+    #[test]
+    fn area_light_needs_cells() {
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        assert_eq!(
+            AreaLight::new(corner, v1, 0, v2, 2, white),
+            Err(ZeroStepsError)
+        );
+        assert_eq!(
+            AreaLight::new(corner, v1, 4, v2, 0, white),
+            Err(ZeroStepsError)
+        );
+    }
+
+    #[test]
+    fn compare_lights() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let light = Light::new(PointLight::new(Point::new(0.0, 0.0, 0.0), white));
+        assert_eq!(light.clone(), light);
+        assert_ne!(
+            light,
+            Light::new(PointLight::new(Point::new(1.0, 0.0, 0.0), white))
+        );
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let area = AreaLight::new(
+            corner,
+            Vector::new(1.0, 0.0, 0.0),
+            1,
+            Vector::new(0.0, 1.0, 0.0),
+            1,
+            white,
+        )
+        .unwrap();
+        assert_ne!(light, Light::new(area));
+    }
+
+    #[test]
+    fn create_spot_light() {
+        let light = SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.0, -2.0, 0.0),
+            PI / 8.0,
+            PI / 4.0,
+            Color::new(1.0, 1.0, 1.0),
+        )
+        .unwrap();
+        assert_eq!(light.position, Point::new(0.0, 10.0, 0.0));
+        assert_eq!(light.direction, Vector::new(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn spot_light_falloff() {
+        let light = SpotLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            PI / 8.0,
+            PI / 4.0,
+            Color::new(1.0, 1.0, 1.0),
+        )
+        .unwrap();
+        let at_angle = |angle: f64| Point::new(5.0 * angle.sin(), 0.0, 5.0 * angle.cos());
+        assert_eq!(light.falloff(at_angle(0.0)), 1.0);
+        assert_eq!(light.falloff(at_angle(PI / 10.0)), 1.0);
+        assert_eq!(light.falloff(at_angle(PI / 3.0)), 0.0);
+        assert_eq!(light.falloff(Point::new(0.0, 0.0, -5.0)), 0.0);
+
+        // Halfway between the cones' cosines, smoothstep gives half
+        let cos_half = ((PI / 8.0).cos() + (PI / 4.0).cos()) / 2.0;
+        assert!((light.falloff(at_angle(cos_half.acos())) - 0.5).abs() < EQUALITY_EPSILON);
+        let falloffs = (0..=10)
+            .map(|i| light.falloff(at_angle(PI / 8.0 + PI / 80.0 * i as f64)))
+            .collect::<Vec<_>>();
+        assert!(falloffs.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn spot_light_samples_carry_falloff() {
+        let light = SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.0, -1.0, 0.0),
+            PI / 8.0,
+            PI / 4.0,
+            Color::new(1.0, 0.5, 0.25),
+        )
+        .unwrap();
+        let inside = LightModel::samples(&light, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(inside[0].direction, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(inside[0].distance, 10.0);
+        assert_eq!(inside[0].intensity, Color::new(1.0, 0.5, 0.25));
+        let outside = LightModel::samples(&light, Point::new(10.0, 0.0, 0.0));
+        assert_eq!(outside[0].intensity, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn spot_light_with_equal_cones_has_hard_edge() {
+        let light = SpotLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            PI / 4.0,
+            PI / 4.0,
+            Color::new(1.0, 1.0, 1.0),
+        )
+        .unwrap();
+        assert_eq!(light.falloff(Point::new(0.9, 0.0, 1.0)), 1.0);
+        assert_eq!(light.falloff(Point::new(1.1, 0.0, 1.0)), 0.0);
+    }
+
+    #[test]
+    fn spot_light_needs_valid_cone() {
+        let position = Point::new(0.0, 0.0, 0.0);
+        let direction = Vector::new(0.0, 0.0, 1.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let invalid = [
+            (direction, PI / 4.0, PI / 8.0),
+            (direction, -PI / 8.0, PI / 4.0),
+            (direction, PI / 8.0, 1.5 * PI),
+            (direction, f64::NAN, PI / 4.0),
+            (Vector::new(0.0, 0.0, 0.0), PI / 8.0, PI / 4.0),
+        ];
+        for (direction, inner, outer) in invalid {
+            assert_eq!(
+                SpotLight::new(position, direction, inner, outer, white),
+                Err(InvalidConeError)
+            );
+        }
+        assert!(SpotLight::new(position, direction, 0.0, PI, white).is_ok());
+    }
+
+    #[test]
+    fn spot_light_falloff_at_its_position() {
+        let light = SpotLight::new(
+            Point::new(1.0, 2.0, 3.0),
+            Vector::new(0.0, 0.0, 1.0),
+            PI / 8.0,
+            PI / 4.0,
+            Color::new(1.0, 1.0, 1.0),
+        )
+        .unwrap();
+        assert_eq!(light.falloff(Point::new(1.0, 2.0, 3.0)), 1.0);
+    }
+
+    #[test]
+    fn directional_light_comes_from_infinity() {
+        let light = DirectionalLight::new(Vector::new(0.0, -2.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(light.direction, Vector::new(0.0, -1.0, 0.0));
+        for point in [Point::new(0.0, 0.0, 0.0), Point::new(100.0, -5.0, 3.0)] {
+            let samples = LightModel::samples(&light, point);
+            assert_eq!(samples.len(), 1);
+            assert_eq!(samples[0].direction, Vector::new(0.0, 1.0, 0.0));
+            assert_eq!(samples[0].distance, f64::INFINITY);
+            assert_eq!(samples[0].intensity, Color::new(1.0, 1.0, 1.0));
+        }
     }
 }
@@ -1,4 +1,4 @@
-use crate::{canvas::Color, lights::PointLight, patterns::Pattern, shapes::Shape, Point, Vector};
+use crate::{canvas::Color, lights::LightSample, patterns::Pattern, shapes::Shape, Point, Vector};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Material {
@@ -11,12 +11,24 @@ pub struct Material {
     pub reflective: f64,
     pub transparaency: f64,
     pub refractive_index: f64,
+    /// Whether the material lets its `transparaency` of the light through to
+    /// surfaces behind it, rather than casting a solid shadow
+    pub transparent_shadows: bool,
+    /// Whether light passing through the material takes on its color
+    pub tinted_shadows: bool,
 }
 
 impl Material {
     pub fn new() -> Self {
         Material::default()
     }
+
+    /// The color of `object` at `point`, from the pattern if there is one
+    pub fn color_at(&self, object: &Shape, point: Point) -> Color {
+        self.pattern
+            .as_ref()
+            .map_or(self.color, |pattern| pattern.at_shape(object, point))
+    }
 }
 
 impl Default for Material {
@@ -31,60 +43,60 @@ impl Default for Material {
             reflective: 0.0,
             transparaency: 0.0,
             refractive_index: 1.0,
+            transparent_shadows: false,
+            tinted_shadows: false,
         }
     }
 }
 
+/// Phong lighting of `point` by light `samples`, of which `light_intensity` reaches it
 pub fn lighting(
     material: &Material,
     object: &Shape,
-    light: &PointLight,
+    samples: &[LightSample],
     point: Point,
     eyev: Vector,
     normal: Vector,
-    in_shadow: bool,
+    light_intensity: Color,
 ) -> Color {
-    let color = material
-        .pattern
-        .as_ref()
-        .map_or(material.color, |pattern| pattern.at_shape(object, point));
-    let effective_color = color * light.intensity;
-    let lightv = (light.position - point).normalize();
+    let color = material.color_at(object, point);
 
-    let ambient = effective_color * material.ambient;
-    if in_shadow {
-        return ambient;
-    }
+    let mut ambient = Color::default();
+    let mut lit = Color::default();
+    for sample in samples {
+        let effective_color = color * sample.intensity;
+        let lightv = sample.direction;
 
-    let diffuse;
-    let specular;
-    let light_dot_normal = Vector::dot(lightv, normal);
-    if light_dot_normal < 0.0 {
-        diffuse = Color::default();
-        specular = Color::default();
-    } else {
-        diffuse = effective_color * material.diffuse * light_dot_normal;
+        ambient = ambient + color * sample.ambient * material.ambient;
+
+        let light_dot_normal = Vector::dot(lightv, normal);
+        if light_dot_normal < 0.0 {
+            continue;
+        }
+        lit = lit + effective_color * material.diffuse * light_dot_normal;
 
         let reflectv = (-lightv).reflect(normal);
         let reflect_dot_eye = Vector::dot(reflectv, eyev);
-        if reflect_dot_eye <= 0.0 {
-            specular = Color::default();
-        } else {
+        if reflect_dot_eye > 0.0 {
             let factor = reflect_dot_eye.powf(material.shininess);
-            specular = light.intensity * material.specular * factor;
+            lit = lit + sample.intensity * material.specular * factor;
         }
     }
 
-    ambient + diffuse + specular
+    let count = samples.len().max(1) as f64;
+    (ambient + lit * light_intensity) * (1.0 / count)
 }
 
 #[cfg(test)]
 mod test {
+    use std::f64::consts::PI;
+
     use crate::{
         canvas::{Color, BLACK, WHITE},
-        lights::PointLight,
+        lights::{AreaLight, Attenuation, Light, PointLight, SpotLight},
         patterns::Stripes,
         shapes::Sphere,
+        world::default_world,
         Point, Vector,
     };
 
@@ -106,15 +118,18 @@ mod test {
         let position = Point::new(0.0, 0.0, 0.0);
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light = Light::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
         let result = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(position),
             position,
             eyev,
             normalv,
-            false,
+            WHITE,
         );
         assert_eq!(result, Color::new(1.9, 1.9, 1.9));
     }
@@ -125,15 +140,18 @@ mod test {
         let position = Point::new(0.0, 0.0, 0.0);
         let eyev = Vector::new(0.0, 2_f64.sqrt() / 2.0, -(2_f64.sqrt()) / 2.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light = Light::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
         let result = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(position),
             position,
             eyev,
             normalv,
-            false,
+            WHITE,
         );
         assert_eq!(result, Color::new(1.0, 1.0, 1.0));
     }
@@ -144,15 +162,18 @@ mod test {
         let position = Point::new(0.0, 0.0, 0.0);
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light = Light::new(PointLight::new(
+            Point::new(0.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
         let result = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(position),
             position,
             eyev,
             normalv,
-            false,
+            WHITE,
         );
         assert_eq!(result, Color::new(0.7364, 0.7364, 0.7364));
     }
@@ -163,15 +184,18 @@ mod test {
         let position = Point::new(0.0, 0.0, 0.0);
         let eyev = Vector::new(0.0, -(2_f64.sqrt()) / 2.0, -(2_f64.sqrt()) / 2.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light = Light::new(PointLight::new(
+            Point::new(0.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
         let result = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(position),
             position,
             eyev,
             normalv,
-            false,
+            WHITE,
         );
         assert_eq!(result, Color::new(1.6364, 1.6364, 1.6364));
     }
@@ -182,15 +206,18 @@ mod test {
         let position = Point::new(0.0, 0.0, 0.0);
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
+        let light = Light::new(PointLight::new(
+            Point::new(0.0, 0.0, 10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
         let result = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(position),
             position,
             eyev,
             normalv,
-            false,
+            WHITE,
         );
         assert_eq!(result, Color::new(0.1, 0.1, 0.1));
     }
@@ -201,16 +228,18 @@ mod test {
         let position = Point::new(0.0, 0.0, 0.0);
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normalv = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
-        let in_shadow = true;
+        let light = Light::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
         let result = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(position),
             position,
             eyev,
             normalv,
-            in_shadow,
+            BLACK,
         );
         assert_eq!(result, Color::new(0.1, 0.1, 0.1));
     }
@@ -226,29 +255,165 @@ mod test {
         };
         let eyev = Vector::new(0.0, 0.0, -1.0);
         let normal = Vector::new(0.0, 0.0, -1.0);
-        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), WHITE);
+        let light = Light::new(PointLight::new(Point::new(0.0, 0.0, -10.0), WHITE));
         let c1 = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(Point::new(0.9, 0.0, 0.0)),
             Point::new(0.9, 0.0, 0.0),
             eyev,
             normal,
-            false,
+            WHITE,
         );
         let c2 = lighting(
             &m,
             &Shape::new(Sphere),
-            &light,
+            &light.samples(Point::new(1.1, 0.0, 0.0)),
             Point::new(1.1, 0.0, 0.0),
             eyev,
             normal,
-            false,
+            WHITE,
         );
         assert_eq!(c1, WHITE);
         assert_eq!(c2, BLACK);
     }
 
+    #[test]
+    fn lighting_uses_light_intensity() {
+        let mut w = default_world();
+        w.lights = vec![Light::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let shape = &mut w.objects_mut()[0];
+        shape.material.ambient = 0.1;
+        shape.material.diffuse = 0.9;
+        shape.material.specular = 0.0;
+        shape.material.color = Color::new(1.0, 1.0, 1.0);
+        let shape = &w.objects()[0];
+        let point = Point::new(0.0, 0.0, -1.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let cases = [
+            (WHITE, Color::new(1.0, 1.0, 1.0)),
+            (Color::new(0.5, 0.5, 0.5), Color::new(0.55, 0.55, 0.55)),
+            (BLACK, Color::new(0.1, 0.1, 0.1)),
+            (Color::new(1.0, 0.5, 0.0), Color::new(1.0, 0.55, 0.1)),
+        ];
+        for (intensity, expected) in cases {
+            let result = lighting(
+                &shape.material,
+                shape,
+                &w.lights[0].samples(point),
+                point,
+                eyev,
+                normalv,
+                intensity,
+            );
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn lighting_samples_area_light() {
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        let mut area = AreaLight::new(corner, v1, 2, v2, 2, Color::new(1.0, 1.0, 1.0)).unwrap();
+        area.jitter = false;
+        let light = Light::new(area);
+        let mut shape = Shape::new(Sphere);
+        shape.material.ambient = 0.1;
+        shape.material.diffuse = 0.9;
+        shape.material.specular = 0.0;
+        shape.material.color = Color::new(1.0, 1.0, 1.0);
+        let eye = Point::new(0.0, 0.0, -5.0);
+        let cases = [
+            (
+                Point::new(0.0, 0.0, -1.0),
+                Color::new(0.9965, 0.9965, 0.9965),
+            ),
+            (
+                Point::new(0.0, 2_f64.sqrt() / 2.0, -(2_f64.sqrt()) / 2.0),
+                Color::new(0.62318, 0.62318, 0.62318),
+            ),
+        ];
+        for (point, expected) in cases {
+            let eyev = (eye - point).normalize();
+            let normalv = Vector::new(point.x, point.y, point.z);
+            let result = lighting(
+                &shape.material,
+                &shape,
+                &light.samples(point),
+                point,
+                eyev,
+                normalv,
+                WHITE,
+            );
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn lighting_attenuated_by_spot_light() {
+        let m = Material::default();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let spot = SpotLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Vector::new(0.0, 0.0, 1.0),
+            PI / 32.0,
+            PI / 16.0,
+            Color::new(1.0, 1.0, 1.0),
+        )
+        .unwrap();
+        let light = Light::new(spot.clone());
+        let shape = Shape::new(Sphere);
+        let cases = [
+            (Point::new(0.0, 0.0, 0.0), Color::new(1.9, 1.9, 1.9)),
+            (Point::new(5.0, 0.0, 0.0), Color::new(0.1, 0.1, 0.1)),
+        ];
+        for (point, expected) in cases {
+            let samples = light.samples(point);
+            let result = lighting(&m, &shape, &samples, point, eyev, normalv, WHITE);
+            assert_eq!(result, expected);
+        }
+
+        // In the penumbra, only direct light is scaled by the falloff
+        let point = Point::new(10.0 * (3.0 * PI / 64.0).tan(), 0.0, 0.0);
+        let falloff = spot.falloff(point);
+        assert!(falloff > 0.0 && falloff < 1.0);
+        let samples = light.samples(point);
+        let unlit = lighting(&m, &shape, &samples, point, eyev, normalv, BLACK);
+        assert_eq!(unlit, Color::new(0.1, 0.1, 0.1));
+        let lit = lighting(&m, &shape, &samples, point, eyev, normalv, WHITE);
+        assert!(lit.difference(&unlit) > 0.0);
+        assert!(lit.difference(&Color::new(1.9, 1.9, 1.9)) > 0.0);
+    }
+
+    #[test]
+    fn lighting_attenuated_by_distance() {
+        let m = Material::default();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let shape = Shape::new(Sphere);
+        let mut near = PointLight::new(Point::new(0.0, 0.0, -1.0), Color::new(1.0, 1.0, 1.0));
+        near.attenuation = Attenuation::InverseSquare;
+        let mut far = near.clone();
+        far.position = Point::new(0.0, 0.0, -1000.0);
+
+        let position = Point::new(0.0, 0.0, 0.0);
+        let lit = |light: PointLight| {
+            let samples = Light::new(light).samples(position);
+            lighting(&m, &shape, &samples, position, eyev, normalv, WHITE)
+        };
+        assert_eq!(lit(near.clone()), Color::new(1.9, 1.9, 1.9));
+        // Ambient light does not fade with distance
+        assert_eq!(lit(far), Color::new(0.1, 0.1, 0.1));
+        near.position = Point::new(0.0, 0.0, -2.0);
+        assert_eq!(lit(near), Color::new(0.55, 0.55, 0.55));
+    }
+
     #[test]
     fn reflectivity_for_default_material() {
         let m = Material::default();
@@ -261,4 +426,11 @@ mod test {
         assert_eq!(m.transparaency, 0.0);
         assert_eq!(m.refractive_index, 1.0);
     }
+
+    #[test]
+    fn default_material_casts_solid_shadows() {
+        let m = Material::default();
+        assert!(!m.transparent_shadows);
+        assert!(!m.tinted_shadows);
+    }
 }
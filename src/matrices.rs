@@ -52,7 +52,7 @@ impl Matrix<2, 2> {
 
     pub fn cofactor(&self, i: usize, j: usize) -> Result<f64, MatrixIndexError> {
         let minor = self.minor(i, j)?;
-        if (i + j) % 2 == 0 {
+        if (i + j).is_multiple_of(2) {
             Ok(minor)
         } else {
             Ok(-minor)
@@ -121,7 +121,7 @@ impl Matrix<3, 3> {
 
     pub fn cofactor(&self, i: usize, j: usize) -> Result<f64, MatrixIndexError> {
         let minor = self.minor(i, j)?;
-        if (i + j) % 2 == 0 {
+        if (i + j).is_multiple_of(2) {
             Ok(minor)
         } else {
             Ok(-minor)
@@ -190,7 +190,7 @@ impl Matrix<4, 4> {
 
     pub fn cofactor(&self, i: usize, j: usize) -> Result<f64, MatrixIndexError> {
         let minor = self.minor(i, j)?;
-        if (i + j) % 2 == 0 {
+        if (i + j).is_multiple_of(2) {
             Ok(minor)
         } else {
             Ok(-minor)
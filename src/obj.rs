@@ -0,0 +1,419 @@
+use crate::{
+    shapes::{Group, Shape, SmoothTriangle, Triangle},
+    Point, Vector,
+};
+
+/// Geometry read from a Wavefront OBJ file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ObjFile {
+    pub vertices: Vec<Point>,
+    pub normals: Vec<Vector>,
+    /// Triangles that appear before any named group
+    pub default_group: Group,
+    /// Named groups, in the order they first appear
+    pub groups: Vec<(String, Group)>,
+    /// Line numbers (starting at 1) of lines that were not understood
+    pub ignored_lines: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    InvalidNumber,
+    InvalidIndex,
+    MissingComponent,
+    TooFewVertices,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line number (starting at 1) of the offending line
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let problem = match self.kind {
+            ParseErrorKind::InvalidNumber => "invalid number",
+            ParseErrorKind::InvalidIndex => "invalid vertex or normal index",
+            ParseErrorKind::MissingComponent => "missing component",
+            ParseErrorKind::TooFewVertices => "face has fewer than three vertices",
+        };
+        write!(f, "line {}: {}", self.line, problem)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+struct FaceVertex {
+    vertex: usize,
+    normal: Option<usize>,
+}
+
+impl ObjFile {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut obj = ObjFile::default();
+        let mut current_group: Option<usize> = None;
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |kind| ParseError {
+                line: line_number,
+                kind,
+            };
+
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            let args = words.collect::<Vec<_>>();
+
+            match keyword {
+                _ if keyword.starts_with('#') => {}
+                "v" => {
+                    let [x, y, z] = parse_triple(&args).map_err(error)?;
+                    obj.vertices.push(Point::new(x, y, z));
+                }
+                "vn" => {
+                    let [x, y, z] = parse_triple(&args).map_err(error)?;
+                    obj.normals.push(Vector::new(x, y, z));
+                }
+                "f" => {
+                    let face = args
+                        .iter()
+                        .map(|arg| obj.parse_face_vertex(arg))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(error)?;
+                    if face.len() < 3 {
+                        return Err(error(ParseErrorKind::TooFewVertices));
+                    }
+
+                    let triangles = obj.fan_triangulation(&face);
+                    let group = match current_group {
+                        Some(index) => &mut obj.groups[index].1,
+                        None => &mut obj.default_group,
+                    };
+                    for triangle in triangles {
+                        group.add_child(triangle);
+                    }
+                }
+                "g" if !args.is_empty() => {
+                    let name = args.join(" ");
+                    current_group = Some(
+                        match obj.groups.iter().position(|(other, _)| *other == name) {
+                            Some(index) => index,
+                            None => {
+                                obj.groups.push((name, Group::default()));
+                                obj.groups.len() - 1
+                            }
+                        },
+                    );
+                }
+                _ => obj.ignored_lines.push(line_number),
+            }
+        }
+
+        Ok(obj)
+    }
+
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, group)| group)
+    }
+
+    /// Collects every triangle into a single group, with each named group
+    /// nested as its own child group
+    pub fn to_group(&self) -> Shape {
+        let mut group = self.default_group.clone();
+        for (_, named) in &self.groups {
+            group.add_child(Shape::new(named.clone()));
+        }
+        Shape::new(group)
+    }
+
+    fn parse_face_vertex(&self, arg: &str) -> Result<FaceVertex, ParseErrorKind> {
+        // Vertices are given as `v`, `v/vt`, `v//vn` or `v/vt/vn`
+        let mut parts = arg.split('/');
+        let vertex = parts.next().ok_or(ParseErrorKind::MissingComponent)?;
+        let vertex = resolve_index(vertex, self.vertices.len())?;
+        let _texture = parts.next();
+        let normal = match parts.next() {
+            Some(normal) if !normal.is_empty() => Some(resolve_index(normal, self.normals.len())?),
+            _ => None,
+        };
+        Ok(FaceVertex { vertex, normal })
+    }
+
+    fn fan_triangulation(&self, face: &[FaceVertex]) -> Vec<Shape> {
+        (1..face.len() - 1)
+            .map(|index| {
+                let corners = [&face[0], &face[index], &face[index + 1]];
+                let [p1, p2, p3] = corners.map(|corner| self.vertices[corner.vertex]);
+                match corners.map(|corner| corner.normal) {
+                    [Some(n1), Some(n2), Some(n3)] => Shape::new(SmoothTriangle::new(
+                        p1,
+                        p2,
+                        p3,
+                        self.normals[n1],
+                        self.normals[n2],
+                        self.normals[n3],
+                    )),
+                    _ => Shape::new(Triangle::new(p1, p2, p3)),
+                }
+            })
+            .collect()
+    }
+}
+
+fn parse_triple(args: &[&str]) -> Result<[f64; 3], ParseErrorKind> {
+    // Vertices may carry an optional w component, which is ignored
+    if args.len() < 3 {
+        return Err(ParseErrorKind::MissingComponent);
+    }
+    let mut triple = [0.0; 3];
+    for (value, arg) in triple.iter_mut().zip(args) {
+        *value = arg.parse().map_err(|_| ParseErrorKind::InvalidNumber)?;
+    }
+    Ok(triple)
+}
+
+/// Converts a 1-based (or negative, relative to the end) OBJ index into an
+/// index into a list of length `len`
+fn resolve_index(index: &str, len: usize) -> Result<usize, ParseErrorKind> {
+    let index = index
+        .parse::<isize>()
+        .map_err(|_| ParseErrorKind::InvalidIndex)?;
+    let resolved = if index < 0 {
+        len as isize + index
+    } else {
+        index - 1
+    };
+    if (0..len as isize).contains(&resolved) {
+        Ok(resolved as usize)
+    } else {
+        Err(ParseErrorKind::InvalidIndex)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shapes::ShapeModel;
+
+    use super::*;
+
+    fn triangle_at(group: &Group, index: usize) -> &Triangle {
+        group.children()[index]
+            .model
+            .as_any()
+            .downcast_ref::<Triangle>()
+            .expect("not a triangle")
+    }
+
+    #[test]
+    fn ignore_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright\n\
+            who traveled much faster than light.\n\
+            She set out one day\n\
+            in a relative way,\n\
+            and came back the previous night.\n";
+        let obj = ObjFile::parse(gibberish).unwrap();
+        assert_eq!(obj.ignored_lines, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = "v -1 1 0\n\
+            v -1.0000 0.5000 0.0000\n\
+            v 1 0 0\n\
+            v 1 1 0\n";
+        let obj = ObjFile::parse(file).unwrap();
+        assert_eq!(obj.vertices[0], Point::new(-1.0, 1.0, 0.0));
+        assert_eq!(obj.vertices[1], Point::new(-1.0, 0.5, 0.0));
+        assert_eq!(obj.vertices[2], Point::new(1.0, 0.0, 0.0));
+        assert_eq!(obj.vertices[3], Point::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn triangle_faces() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            \n\
+            f 1 2 3\n\
+            f 1 3 4\n";
+        let obj = ObjFile::parse(file).unwrap();
+        let t1 = triangle_at(&obj.default_group, 0);
+        let t2 = triangle_at(&obj.default_group, 1);
+        assert_eq!(t1.p1(), obj.vertices[0]);
+        assert_eq!(t1.p2(), obj.vertices[1]);
+        assert_eq!(t1.p3(), obj.vertices[2]);
+        assert_eq!(t2.p1(), obj.vertices[0]);
+        assert_eq!(t2.p2(), obj.vertices[2]);
+        assert_eq!(t2.p3(), obj.vertices[3]);
+    }
+
+    #[test]
+    fn triangulate_polygons() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            v 0 2 0\n\
+            \n\
+            f 1 2 3 4 5\n";
+        let obj = ObjFile::parse(file).unwrap();
+        assert_eq!(obj.default_group.children().len(), 3);
+        let t1 = triangle_at(&obj.default_group, 0);
+        let t2 = triangle_at(&obj.default_group, 1);
+        let t3 = triangle_at(&obj.default_group, 2);
+        assert_eq!(t1.p1(), obj.vertices[0]);
+        assert_eq!(t1.p2(), obj.vertices[1]);
+        assert_eq!(t1.p3(), obj.vertices[2]);
+        assert_eq!(t2.p1(), obj.vertices[0]);
+        assert_eq!(t2.p2(), obj.vertices[2]);
+        assert_eq!(t2.p3(), obj.vertices[3]);
+        assert_eq!(t3.p1(), obj.vertices[0]);
+        assert_eq!(t3.p2(), obj.vertices[3]);
+        assert_eq!(t3.p3(), obj.vertices[4]);
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            \n\
+            g FirstGroup\n\
+            f 1 2 3\n\
+            g SecondGroup\n\
+            f 1 3 4\n";
+        let obj = ObjFile::parse(file).unwrap();
+        let t1 = triangle_at(obj.group("FirstGroup").unwrap(), 0);
+        let t2 = triangle_at(obj.group("SecondGroup").unwrap(), 0);
+        assert_eq!(t1.p1(), obj.vertices[0]);
+        assert_eq!(t1.p2(), obj.vertices[1]);
+        assert_eq!(t1.p3(), obj.vertices[2]);
+        assert_eq!(t2.p1(), obj.vertices[0]);
+        assert_eq!(t2.p2(), obj.vertices[2]);
+        assert_eq!(t2.p3(), obj.vertices[3]);
+    }
+
+    #[test]
+    fn convert_obj_to_group() {
+        let file = "v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            \n\
+            g FirstGroup\n\
+            f 1 2 3\n\
+            g SecondGroup\n\
+            f 1 3 4\n";
+        let obj = ObjFile::parse(file).unwrap();
+        let g = obj.to_group();
+        let children = g.model.children();
+        assert_eq!(children.len(), 2);
+        assert_eq!(
+            children[0].model.as_any().downcast_ref::<Group>(),
+            obj.group("FirstGroup")
+        );
+        assert_eq!(
+            children[1].model.as_any().downcast_ref::<Group>(),
+            obj.group("SecondGroup")
+        );
+    }
+
+    #[test]
+    fn vertex_normal_records() {
+        let file = "vn 0 0 1\n\
+            vn 0.707 0 -0.707\n\
+            vn 1 2 3\n";
+        let obj = ObjFile::parse(file).unwrap();
+        assert_eq!(obj.normals[0], Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(obj.normals[1], Vector::new(0.707, 0.0, -0.707));
+        assert_eq!(obj.normals[2], Vector::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let file = "v 0 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            \n\
+            vn -1 0 0\n\
+            vn 1 0 0\n\
+            vn 0 1 0\n\
+            \n\
+            f 1//3 2//1 3//2\n\
+            f 1/0/3 2/102/1 3/14/2\n";
+        let obj = ObjFile::parse(file).unwrap();
+        let children = obj.default_group.children();
+        let t1 = children[0]
+            .model
+            .as_any()
+            .downcast_ref::<SmoothTriangle>()
+            .unwrap();
+        let t2 = children[1]
+            .model
+            .as_any()
+            .downcast_ref::<SmoothTriangle>()
+            .unwrap();
+        assert_eq!(t1.p1(), obj.vertices[0]);
+        assert_eq!(t1.p2(), obj.vertices[1]);
+        assert_eq!(t1.p3(), obj.vertices[2]);
+        assert_eq!(t1.n1(), obj.normals[2]);
+        assert_eq!(t1.n2(), obj.normals[0]);
+        assert_eq!(t1.n3(), obj.normals[1]);
+        assert_eq!(t1, t2);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_not_reported() {
+        let file = "# a comment\n\
+            \n\
+            v 0 1 0\n\
+            vt 0.5 0.5\n";
+        let obj = ObjFile::parse(file).unwrap();
+        assert_eq!(obj.ignored_lines, vec![4]);
+    }
+
+    #[test]
+    fn negative_indices_are_relative() {
+        let file = "v 0 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            f -3 -2 -1\n";
+        let obj = ObjFile::parse(file).unwrap();
+        let t = triangle_at(&obj.default_group, 0);
+        assert_eq!(t.p1(), obj.vertices[0]);
+        assert_eq!(t.p3(), obj.vertices[2]);
+    }
+
+    #[test]
+    fn errors_report_line_numbers() {
+        let examples = [
+            ("v 0 1\n", 1, ParseErrorKind::MissingComponent),
+            ("v 0 1 0\nv a b c\n", 2, ParseErrorKind::InvalidNumber),
+            (
+                "v 0 1 0\nv 1 0 0\nf 1 2\n",
+                3,
+                ParseErrorKind::TooFewVertices,
+            ),
+            ("v 0 1 0\n\nf 1 2 3\n", 3, ParseErrorKind::InvalidIndex),
+            ("v 0 1 0\nf 1//1 1 1\n", 2, ParseErrorKind::InvalidIndex),
+        ];
+        for (file, line, kind) in examples {
+            assert_eq!(ObjFile::parse(file), Err(ParseError { line, kind }));
+        }
+    }
+
+    #[test]
+    fn errors_display_line_and_problem() {
+        let error = ObjFile::parse("v 0 1 0\nv a b c\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: invalid number");
+    }
+}
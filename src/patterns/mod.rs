@@ -17,11 +17,11 @@ use crate::{
     Point,
 };
 
-pub trait PatternModel: Clone + Debug + PartialEq + 'static {
+pub trait PatternModel: Clone + Debug + PartialEq + Send + Sync + 'static {
     fn at(&self, point: Point) -> Color;
 }
 
-trait DynamicPatternModel: Debug {
+trait DynamicPatternModel: Debug + Send + Sync {
     fn at(&self, point: Point) -> Color;
 
     fn as_any(&self) -> &dyn Any;
@@ -48,7 +48,7 @@ impl<T: PatternModel> DynamicPatternModel for T {
         other
             .as_any()
             .downcast_ref::<Self>()
-            .map_or(false, |other| self == other)
+            .is_some_and(|other| self == other)
     }
 }
 
@@ -78,7 +78,7 @@ impl Pattern {
     }
 
     pub fn at_shape(&self, shape: &Shape, point: Point) -> Color {
-        let shape_point = shape.get_inverse_transform() * point;
+        let shape_point = shape.world_to_object(point);
         let pattern_point = &self.inverse * shape_point;
         self.model.at(pattern_point)
     }
@@ -102,6 +102,17 @@ impl PartialEq for Pattern {
     }
 }
 
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TestPattern;
+
+#[cfg(test)]
+impl PatternModel for TestPattern {
+    fn at(&self, point: Point) -> Color {
+        Color::new(point.x, point.y, point.z)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
@@ -112,15 +123,6 @@ mod test {
 
     use super::*;
 
-    #[derive(Debug, Clone, PartialEq)]
-    struct TestPattern;
-
-    impl PatternModel for TestPattern {
-        fn at(&self, point: Point) -> Color {
-            Color::new(point.x, point.y, point.z)
-        }
-    }
-
     #[test]
     fn default_pattern() {
         let pattern = Pattern::new(TestPattern);
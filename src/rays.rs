@@ -3,15 +3,36 @@ use crate::{
     tuples::{Point, Vector},
 };
 
+/// What a ray is cast for, which decides the shapes it can see
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RayKind {
+    /// Seen by the camera, directly or through refraction
+    #[default]
+    Camera,
+    /// Bounced off a reflective surface
+    Reflection,
+    /// Looking for anything between a point and a light
+    Shadow,
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Ray {
     pub origin: Point,
     pub direction: Vector,
+    pub kind: RayKind,
 }
 
 impl Ray {
     pub fn new(origin: Point, direction: Vector) -> Self {
-        Ray { origin, direction }
+        Ray::with_kind(origin, direction, RayKind::Camera)
+    }
+
+    pub fn with_kind(origin: Point, direction: Vector, kind: RayKind) -> Self {
+        Ray {
+            origin,
+            direction,
+            kind,
+        }
     }
 
     pub fn position(&self, t: f64) -> Point {
@@ -22,6 +43,7 @@ impl Ray {
         Ray {
             origin: transform * self.origin,
             direction: transform * self.direction,
+            kind: self.kind,
         }
     }
 }
@@ -42,6 +64,9 @@ mod test {
         let r = Ray::new(origin, direction);
         assert_eq!(r.origin, origin);
         assert_eq!(r.direction, direction);
+        assert_eq!(r.kind, RayKind::Camera);
+        let r = Ray::with_kind(origin, direction, RayKind::Reflection);
+        assert_eq!(r.kind, RayKind::Reflection);
     }
 
     #[test]
@@ -70,4 +95,15 @@ mod test {
         assert_eq!(r2.origin, Point::new(2.0, 6.0, 12.0));
         assert_eq!(r2.direction, Vector::new(0.0, 3.0, 0.0));
     }
+
+    #[test]
+    fn transform_keeps_kind() {
+        let r = Ray::with_kind(
+            Point::new(1.0, 2.0, 3.0),
+            Vector::new(0.0, 1.0, 0.0),
+            RayKind::Shadow,
+        );
+        let r2 = r.transformed(&translation(3.0, 4.0, 5.0));
+        assert_eq!(r2.kind, RayKind::Shadow);
+    }
 }
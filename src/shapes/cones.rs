@@ -0,0 +1,231 @@
+use crate::{bounds::BoundingBox, rays::Ray, Point, Vector, EQUALITY_EPSILON};
+
+use super::{cylinders::within_cap, ShapeModel};
+
+const PARALLEL_EPSILON: f64 = 0.00001;
+
+/// A double-napped cone around the y axis with its apex at the origin and a
+/// radius equal to `|y|`, optionally truncated at `minimum` and `maximum`
+/// (exclusive) and capped at both ends if `closed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cone {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cone {
+    pub fn new(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Cone {
+            minimum,
+            maximum,
+            closed,
+        }
+    }
+
+    fn intersect_caps(&self, local_ray: &Ray, xs: &mut Vec<f64>) {
+        if !self.closed || local_ray.direction.y.abs() < PARALLEL_EPSILON {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - local_ray.origin.y) / local_ray.direction.y;
+            if within_cap(local_ray, t, y.abs()) {
+                xs.push(t);
+            }
+        }
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Cone {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl ShapeModel for Cone {
+    fn local_intersect(&self, local_ray: &Ray) -> Vec<f64> {
+        let mut xs = vec![];
+
+        let origin = local_ray.origin;
+        let direction = local_ray.direction;
+        let a = direction.x * direction.x - direction.y * direction.y + direction.z * direction.z;
+        let b = 2.0 * origin.x * direction.x - 2.0 * origin.y * direction.y
+            + 2.0 * origin.z * direction.z;
+        let c = origin.x * origin.x - origin.y * origin.y + origin.z * origin.z;
+
+        let mut ts = vec![];
+        if a.abs() < PARALLEL_EPSILON {
+            // The ray is parallel to one of the cone's halves, so it can hit
+            // at most the other half
+            if b.abs() >= PARALLEL_EPSILON {
+                ts.push(-c / (2.0 * b));
+            }
+        } else {
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return vec![];
+            }
+
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+            ts.push(t0.min(t1));
+            ts.push(t0.max(t1));
+        }
+
+        for t in ts {
+            let y = origin.y + t * direction.y;
+            if self.minimum < y && y < self.maximum {
+                xs.push(t);
+            }
+        }
+
+        self.intersect_caps(local_ray, &mut xs);
+        xs
+    }
+
+    fn local_normal_at(&self, local_point: Point) -> Vector {
+        let distance = local_point.x * local_point.x + local_point.z * local_point.z;
+        let radius = local_point.y * local_point.y;
+
+        if distance < radius && local_point.y >= self.maximum - EQUALITY_EPSILON {
+            Vector::new(0.0, 1.0, 0.0)
+        } else if distance < radius && local_point.y <= self.minimum + EQUALITY_EPSILON {
+            Vector::new(0.0, -1.0, 0.0)
+        } else if distance == 0.0 && local_point.y == 0.0 {
+            // The apex, where the two halves meet and the surface has no
+            // slope to take a normal from
+            Vector::new(0.0, 1.0, 0.0)
+        } else {
+            let y = distance.sqrt();
+            let y = if local_point.y > 0.0 { -y } else { y };
+            Vector::new(local_point.x, y, local_point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let radius = self.minimum.abs().max(self.maximum.abs());
+        BoundingBox::new(
+            Point::new(-radius, self.minimum, -radius),
+            Point::new(radius, self.maximum, radius),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shapes::Shape;
+
+    use super::*;
+
+    #[test]
+    fn intersect_cone() {
+        let examples = [
+            (
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                5.0,
+                5.0,
+            ),
+            (
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(1.0, 1.0, 1.0),
+                8.66025,
+                8.66025,
+            ),
+            (
+                Point::new(1.0, 1.0, -5.0),
+                Vector::new(-0.5, -1.0, 1.0),
+                4.55006,
+                49.44994,
+            ),
+        ];
+        let shape = Cone::default();
+        for (origin, direction, t0, t1) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            let xs = shape.local_intersect(&r);
+            assert_eq!(xs.len(), 2);
+            assert!((xs[0] - t0).abs() < EQUALITY_EPSILON);
+            assert!((xs[1] - t1).abs() < EQUALITY_EPSILON);
+        }
+    }
+
+    #[test]
+    fn intersect_cone_ray_parallel_to_one_half() {
+        let shape = Cone::default();
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(0.0, 1.0, 1.0).normalize(),
+        );
+        let xs = shape.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!((xs[0] - 0.35355).abs() < EQUALITY_EPSILON);
+    }
+
+    #[test]
+    fn intersect_cone_end_caps() {
+        let examples = [
+            (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0), 0),
+            (Point::new(0.0, 0.0, -0.25), Vector::new(0.0, 1.0, 1.0), 2),
+            (Point::new(0.0, 0.0, -0.25), Vector::new(0.0, 1.0, 0.0), 4),
+        ];
+        let shape = Cone::new(-0.5, 0.5, true);
+        for (origin, direction, count) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            let xs = shape.local_intersect(&r);
+            assert_eq!(xs.len(), count);
+        }
+    }
+
+    #[test]
+    fn cone_bounds() {
+        let b = Cone::default().bounds();
+        assert_eq!(b.min.x, f64::NEG_INFINITY);
+        assert_eq!(b.min.y, f64::NEG_INFINITY);
+        assert_eq!(b.max.y, f64::INFINITY);
+        assert_eq!(b.max.z, f64::INFINITY);
+
+        let b = Cone::new(-5.0, 3.0, true).bounds();
+        assert_eq!(b.min, Point::new(-5.0, -5.0, -5.0));
+        assert_eq!(b.max, Point::new(5.0, 3.0, 5.0));
+    }
+
+    #[test]
+    fn normal_on_cone() {
+        let examples = [
+            (Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (
+                Point::new(1.0, 1.0, 1.0),
+                Vector::new(1.0, -(2_f64.sqrt()), 1.0),
+            ),
+            (Point::new(-1.0, -1.0, 0.0), Vector::new(-1.0, 1.0, 0.0)),
+        ];
+        let shape = Cone::default();
+        for (point, normal) in examples {
+            assert_eq!(shape.local_normal_at(point), normal);
+        }
+    }
+
+    #[test]
+    fn normal_at_apex_is_finite() {
+        let shape = Shape::new(Cone::default());
+        let normal = shape.normal_at(Point::new(0.0, 0.0, 0.0));
+        assert_eq!(normal, Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn normal_on_cone_end_caps() {
+        let examples = [
+            (Point::new(0.0, -1.0, 0.5), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.5, 1.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+        ];
+        let shape = Cone::new(-1.0, 1.0, true);
+        for (point, normal) in examples {
+            assert_eq!(shape.local_normal_at(point), normal);
+        }
+    }
+}
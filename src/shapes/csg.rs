@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+use crate::{bounds::BoundingBox, intersections::Intersections, rays::Ray, Point, Vector};
+
+use super::{Shape, ShapeModel};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    /// Whether an intersection belongs on the surface of the combined shape,
+    /// given whether it hit the left shape and whether it lies inside each
+    /// of the shapes
+    pub fn intersection_allowed(&self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+        }
+    }
+}
+
+/// Constructive solid geometry, combining two shapes with a set operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Csg {
+    operation: CsgOperation,
+    children: [Shape; 2],
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Shape, right: Shape) -> Self {
+        Csg {
+            operation,
+            children: [left, right],
+        }
+    }
+
+    pub fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    pub fn left(&self) -> &Shape {
+        &self.children[0]
+    }
+
+    pub fn right(&self) -> &Shape {
+        &self.children[1]
+    }
+}
+
+impl ShapeModel for Csg {
+    fn local_intersect(&self, _local_ray: &Ray) -> Vec<f64> {
+        // The surface of a CSG shape is made up of the surfaces of its
+        // children, which are intersected by `Shape::intersect`
+        vec![]
+    }
+
+    fn local_normal_at(&self, _local_point: Point) -> Vector {
+        unreachable!("CSG shapes have no surface to compute a normal for")
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = self.left().parent_space_bounds();
+        bounds.merge(&self.right().parent_space_bounds());
+        bounds
+    }
+
+    fn children(&self) -> &[Shape] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut [Shape] {
+        &mut self.children
+    }
+
+    fn filter_intersections<'shape>(
+        &self,
+        intersections: Intersections<'shape>,
+    ) -> Intersections<'shape> {
+        let mut left_shapes = HashSet::new();
+        collect_shapes(self.left(), &mut left_shapes);
+
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut filtered = vec![];
+        for intersection in intersections {
+            let left_hit = left_shapes.contains(&(intersection.object as *const Shape));
+            if self
+                .operation
+                .intersection_allowed(left_hit, in_left, in_right)
+            {
+                filtered.push(intersection);
+            }
+
+            if left_hit {
+                in_left = !in_left;
+            } else {
+                in_right = !in_right;
+            }
+        }
+        Intersections::new(filtered)
+    }
+}
+
+/// Adds `shape` and all of its descendants to `shapes`, by address
+fn collect_shapes(shape: &Shape, shapes: &mut HashSet<*const Shape>) {
+    shapes.insert(shape);
+    for child in shape.model.children() {
+        collect_shapes(child, shapes);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        intersections::Intersection,
+        shapes::{Cube, Group, Sphere},
+        transformations::translation,
+    };
+
+    use super::*;
+
+    #[test]
+    fn create_csg() {
+        let s1 = Shape::new(Sphere);
+        let s2 = Shape::new(Cube);
+        let c = Csg::new(CsgOperation::Union, s1.clone(), s2.clone());
+        assert_eq!(c.operation(), CsgOperation::Union);
+        assert_eq!(*c.left(), s1);
+        assert_eq!(*c.right(), s2);
+    }
+
+    #[test]
+    fn evaluate_rules() {
+        use CsgOperation::*;
+        let examples = [
+            (Union, true, true, true, false),
+            (Union, true, true, false, true),
+            (Union, true, false, true, false),
+            (Union, true, false, false, true),
+            (Union, false, true, true, false),
+            (Union, false, true, false, false),
+            (Union, false, false, true, true),
+            (Union, false, false, false, true),
+            (Intersection, true, true, true, true),
+            (Intersection, true, true, false, false),
+            (Intersection, true, false, true, true),
+            (Intersection, true, false, false, false),
+            (Intersection, false, true, true, true),
+            (Intersection, false, true, false, true),
+            (Intersection, false, false, true, false),
+            (Intersection, false, false, false, false),
+            (Difference, true, true, true, false),
+            (Difference, true, true, false, true),
+            (Difference, true, false, true, false),
+            (Difference, true, false, false, true),
+            (Difference, false, true, true, true),
+            (Difference, false, true, false, true),
+            (Difference, false, false, true, false),
+            (Difference, false, false, false, false),
+        ];
+        for (operation, left_hit, in_left, in_right, result) in examples {
+            assert_eq!(
+                operation.intersection_allowed(left_hit, in_left, in_right),
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn filter_list_of_intersections() {
+        let examples = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+        for (operation, x0, x1) in examples {
+            let c = Shape::new(Csg::new(operation, Shape::new(Sphere), Shape::new(Cube)));
+            let (s1, s2) = (&c.model.children()[0], &c.model.children()[1]);
+            let xs = Intersections::new(vec![
+                Intersection::new(1.0, s1),
+                Intersection::new(2.0, s2),
+                Intersection::new(3.0, s1),
+                Intersection::new(4.0, s2),
+            ]);
+            let result = c.model.filter_intersections(xs.clone());
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0], xs[x0]);
+            assert_eq!(result[1], xs[x1]);
+        }
+    }
+
+    #[test]
+    fn filter_intersections_with_nested_children() {
+        let left = Shape::new(Group::new(vec![Shape::new(Sphere)]));
+        let c = Shape::new(Csg::new(CsgOperation::Difference, left, Shape::new(Cube)));
+        let s1 = &c.model.children()[0].model.children()[0];
+        let s2 = &c.model.children()[1];
+        let xs = Intersections::new(vec![
+            Intersection::new(1.0, s1),
+            Intersection::new(2.0, s2),
+            Intersection::new(3.0, s1),
+            Intersection::new(4.0, s2),
+        ]);
+        let result = c.model.filter_intersections(xs.clone());
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], xs[0]);
+        assert_eq!(result[1], xs[1]);
+    }
+
+    #[test]
+    fn ray_misses_csg() {
+        let c = Shape::new(Csg::new(
+            CsgOperation::Union,
+            Shape::new(Sphere),
+            Shape::new(Cube),
+        ));
+        let r = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(c.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_hits_csg() {
+        let s1 = Shape::new(Sphere);
+        let mut s2 = Shape::new(Sphere);
+        s2.set_transform(translation(0.0, 0.0, 0.5)).unwrap();
+        let c = Shape::new(Csg::new(CsgOperation::Union, s1, s2));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = c.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert!(std::ptr::eq(xs[0].object, &c.model.children()[0]));
+        assert_eq!(xs[1].t, 6.5);
+        assert!(std::ptr::eq(xs[1].object, &c.model.children()[1]));
+    }
+
+    #[test]
+    fn csg_bounds_contain_children() {
+        let left = Shape::new(Sphere);
+        let mut right = Shape::new(Sphere);
+        right.set_transform(translation(2.0, 3.0, 4.0)).unwrap();
+        let c = Csg::new(CsgOperation::Difference, left, right);
+        let b = c.bounds();
+        assert_eq!(b.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(b.max, Point::new(3.0, 4.0, 5.0));
+    }
+}
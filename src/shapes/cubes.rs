@@ -0,0 +1,180 @@
+use crate::{bounds::BoundingBox, rays::Ray, Point, Vector};
+
+use super::ShapeModel;
+
+const PARALLEL_EPSILON: f64 = 0.00001;
+
+/// An axis-aligned cube spanning -1 to 1 on every axis
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Cube;
+
+/// Returns the t values where a ray enters and leaves the slab between -1 and
+/// 1 along a single axis
+fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+    let tmin_numerator = -1.0 - origin;
+    let tmax_numerator = 1.0 - origin;
+
+    let (tmin, tmax) = if direction.abs() >= PARALLEL_EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl ShapeModel for Cube {
+    fn local_intersect(&self, local_ray: &Ray) -> Vec<f64> {
+        let (xtmin, xtmax) = check_axis(local_ray.origin.x, local_ray.direction.x);
+        let (ytmin, ytmax) = check_axis(local_ray.origin.y, local_ray.direction.y);
+        let (ztmin, ztmax) = check_axis(local_ray.origin.z, local_ray.direction.z);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            vec![]
+        } else {
+            vec![tmin, tmax]
+        }
+    }
+
+    fn local_normal_at(&self, local_point: Point) -> Vector {
+        let x = local_point.x.abs();
+        let y = local_point.y.abs();
+        let z = local_point.z.abs();
+        let maxc = x.max(y).max(z);
+
+        if maxc == x {
+            Vector::new(local_point.x, 0.0, 0.0)
+        } else if maxc == y {
+            Vector::new(0.0, local_point.y, 0.0)
+        } else {
+            Vector::new(0.0, 0.0, local_point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ray_intersects_cube() {
+        let examples = [
+            (
+                Point::new(5.0, 0.5, 0.0),
+                Vector::new(-1.0, 0.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(-5.0, 0.5, 0.0),
+                Vector::new(1.0, 0.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 5.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, -5.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 0.0, 5.0),
+                Vector::new(0.0, 0.0, -1.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.0, 0.5, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+                -1.0,
+                1.0,
+            ),
+        ];
+        let c = Cube;
+        for (origin, direction, t1, t2) in examples {
+            let r = Ray::new(origin, direction);
+            let xs = c.local_intersect(&r);
+            assert_eq!(xs.len(), 2);
+            assert_eq!(xs[0], t1);
+            assert_eq!(xs[1], t2);
+        }
+    }
+
+    #[test]
+    fn ray_misses_cube() {
+        let examples = [
+            (
+                Point::new(-2.0, 0.0, 0.0),
+                Vector::new(0.2673, 0.5345, 0.8018),
+            ),
+            (
+                Point::new(0.0, -2.0, 0.0),
+                Vector::new(0.8018, 0.2673, 0.5345),
+            ),
+            (
+                Point::new(0.0, 0.0, -2.0),
+                Vector::new(0.5345, 0.8018, 0.2673),
+            ),
+            (Point::new(2.0, 0.0, 2.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(0.0, 2.0, 2.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(2.0, 2.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+        let c = Cube;
+        for (origin, direction) in examples {
+            let r = Ray::new(origin, direction);
+            let xs = c.local_intersect(&r);
+            assert!(xs.is_empty());
+        }
+    }
+
+    #[test]
+    fn cube_bounds() {
+        let b = Cube.bounds();
+        assert_eq!(b.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(b.max, Point::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn normal_on_surface_of_cube() {
+        let examples = [
+            (Point::new(1.0, 0.5, -0.8), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(-1.0, -0.2, 0.9), Vector::new(-1.0, 0.0, 0.0)),
+            (Point::new(-0.4, 1.0, -0.1), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.3, -1.0, -0.7), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(-0.6, 0.3, 1.0), Vector::new(0.0, 0.0, 1.0)),
+            (Point::new(0.4, 0.4, -1.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(1.0, 1.0, 1.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(-1.0, -1.0, -1.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+        let c = Cube;
+        for (point, normal) in examples {
+            assert_eq!(c.local_normal_at(point), normal);
+        }
+    }
+}
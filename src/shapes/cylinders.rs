@@ -0,0 +1,245 @@
+use crate::{bounds::BoundingBox, rays::Ray, Point, Vector, EQUALITY_EPSILON};
+
+use super::ShapeModel;
+
+const PARALLEL_EPSILON: f64 = 0.00001;
+
+/// A cylinder of radius 1 around the y axis, optionally truncated at
+/// `minimum` and `maximum` (exclusive) and capped at both ends if `closed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cylinder {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cylinder {
+    pub fn new(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Cylinder {
+            minimum,
+            maximum,
+            closed,
+        }
+    }
+
+    fn intersect_caps(&self, local_ray: &Ray, xs: &mut Vec<f64>) {
+        if !self.closed || local_ray.direction.y.abs() < PARALLEL_EPSILON {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - local_ray.origin.y) / local_ray.direction.y;
+            if within_cap(local_ray, t, 1.0) {
+                xs.push(t);
+            }
+        }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Cylinder {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+/// Checks whether the intersection at `t` lies within `radius` of the y axis
+pub(super) fn within_cap(local_ray: &Ray, t: f64, radius: f64) -> bool {
+    let x = local_ray.origin.x + t * local_ray.direction.x;
+    let z = local_ray.origin.z + t * local_ray.direction.z;
+    x * x + z * z <= radius * radius
+}
+
+impl ShapeModel for Cylinder {
+    fn local_intersect(&self, local_ray: &Ray) -> Vec<f64> {
+        let mut xs = vec![];
+
+        let a = local_ray.direction.x * local_ray.direction.x
+            + local_ray.direction.z * local_ray.direction.z;
+        // Rays parallel to the y axis can only hit the caps
+        if a.abs() >= PARALLEL_EPSILON {
+            let b = 2.0 * local_ray.origin.x * local_ray.direction.x
+                + 2.0 * local_ray.origin.z * local_ray.direction.z;
+            let c = local_ray.origin.x * local_ray.origin.x
+                + local_ray.origin.z * local_ray.origin.z
+                - 1.0;
+
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return vec![];
+            }
+
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+            for t in [t0, t1] {
+                let y = local_ray.origin.y + t * local_ray.direction.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(t);
+                }
+            }
+        }
+
+        self.intersect_caps(local_ray, &mut xs);
+        xs
+    }
+
+    fn local_normal_at(&self, local_point: Point) -> Vector {
+        let distance = local_point.x * local_point.x + local_point.z * local_point.z;
+
+        if distance < 1.0 && local_point.y >= self.maximum - EQUALITY_EPSILON {
+            Vector::new(0.0, 1.0, 0.0)
+        } else if distance < 1.0 && local_point.y <= self.minimum + EQUALITY_EPSILON {
+            Vector::new(0.0, -1.0, 0.0)
+        } else {
+            Vector::new(local_point.x, 0.0, local_point.z)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(-1.0, self.minimum, -1.0),
+            Point::new(1.0, self.maximum, 1.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ray_misses_cylinder() {
+        let examples = [
+            (Point::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, -5.0), Vector::new(1.0, 1.0, 1.0)),
+        ];
+        let cyl = Cylinder::default();
+        for (origin, direction) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            let xs = cyl.local_intersect(&r);
+            assert!(xs.is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_strikes_cylinder() {
+        let examples = [
+            (
+                Point::new(1.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                5.0,
+                5.0,
+            ),
+            (
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+                4.0,
+                6.0,
+            ),
+            (
+                Point::new(0.5, 0.0, -5.0),
+                Vector::new(0.1, 1.0, 1.0),
+                6.80798,
+                7.08872,
+            ),
+        ];
+        let cyl = Cylinder::default();
+        for (origin, direction, t0, t1) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            let xs = cyl.local_intersect(&r);
+            assert_eq!(xs.len(), 2);
+            assert!((xs[0] - t0).abs() < EQUALITY_EPSILON);
+            assert!((xs[1] - t1).abs() < EQUALITY_EPSILON);
+        }
+    }
+
+    #[test]
+    fn normal_on_cylinder() {
+        let examples = [
+            (Point::new(1.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(0.0, 5.0, -1.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(0.0, -2.0, 1.0), Vector::new(0.0, 0.0, 1.0)),
+            (Point::new(-1.0, 1.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+        let cyl = Cylinder::default();
+        for (point, normal) in examples {
+            assert_eq!(cyl.local_normal_at(point), normal);
+        }
+    }
+
+    #[test]
+    fn default_minimum_and_maximum() {
+        let cyl = Cylinder::default();
+        assert_eq!(cyl.minimum, f64::NEG_INFINITY);
+        assert_eq!(cyl.maximum, f64::INFINITY);
+        assert!(!cyl.closed);
+    }
+
+    #[test]
+    fn cylinder_bounds() {
+        let b = Cylinder::default().bounds();
+        assert_eq!(b.min.x, -1.0);
+        assert_eq!(b.min.y, f64::NEG_INFINITY);
+        assert_eq!(b.max.y, f64::INFINITY);
+        assert_eq!(b.max.z, 1.0);
+
+        let b = Cylinder::new(-5.0, 3.0, true).bounds();
+        assert_eq!(b.min, Point::new(-1.0, -5.0, -1.0));
+        assert_eq!(b.max, Point::new(1.0, 3.0, 1.0));
+    }
+
+    #[test]
+    fn intersect_constrained_cylinder() {
+        let examples = [
+            (Point::new(0.0, 1.5, 0.0), Vector::new(0.1, 1.0, 0.0), 0),
+            (Point::new(0.0, 3.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 1.5, -2.0), Vector::new(0.0, 0.0, 1.0), 2),
+        ];
+        let cyl = Cylinder::new(1.0, 2.0, false);
+        for (origin, direction, count) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            let xs = cyl.local_intersect(&r);
+            assert_eq!(xs.len(), count);
+        }
+    }
+
+    #[test]
+    fn intersect_caps_of_closed_cylinder() {
+        let examples = [
+            (Point::new(0.0, 3.0, 0.0), Vector::new(0.0, -1.0, 0.0), 2),
+            (Point::new(0.0, 3.0, -2.0), Vector::new(0.0, -1.0, 2.0), 2),
+            (Point::new(0.0, 4.0, -2.0), Vector::new(0.0, -1.0, 1.0), 2),
+            (Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 1.0, 2.0), 2),
+            (Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 1.0), 2),
+        ];
+        let cyl = Cylinder::new(1.0, 2.0, true);
+        for (origin, direction, count) in examples {
+            let r = Ray::new(origin, direction.normalize());
+            let xs = cyl.local_intersect(&r);
+            assert_eq!(xs.len(), count);
+        }
+    }
+
+    #[test]
+    fn normal_on_cylinder_end_caps() {
+        let examples = [
+            (Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.5, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.0, 1.0, 0.5), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(0.0, 2.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.5, 2.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 2.0, 0.5), Vector::new(0.0, 1.0, 0.0)),
+        ];
+        let cyl = Cylinder::new(1.0, 2.0, true);
+        for (point, normal) in examples {
+            assert_eq!(cyl.local_normal_at(point), normal);
+        }
+    }
+}
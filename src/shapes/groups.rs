@@ -0,0 +1,201 @@
+use std::sync::OnceLock;
+
+use crate::{bounds::BoundingBox, bvh::Bvh, rays::Ray, Point, Vector};
+
+use super::{Shape, ShapeModel};
+
+/// A collection of shapes that are transformed together with the group
+#[derive(Debug, Default, Clone)]
+pub struct Group {
+    children: Vec<Shape>,
+    /// Built on first use, and discarded whenever the children may change
+    bvh: OnceLock<Bvh>,
+}
+
+impl Group {
+    pub fn new(children: Vec<Shape>) -> Self {
+        Group {
+            children,
+            bvh: OnceLock::new(),
+        }
+    }
+
+    /// Adds `child` as it is. Flags set through the enclosing shape, such as
+    /// whether it casts shadows, only reach the children it had at the time.
+    pub fn add_child(&mut self, child: Shape) {
+        self.bvh.take();
+        self.children.push(child);
+    }
+
+    fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let bounds = self
+                .children
+                .iter()
+                .map(|child| child.parent_space_bounds())
+                .collect::<Vec<_>>();
+            Bvh::new(&bounds)
+        })
+    }
+}
+
+impl PartialEq for Group {
+    fn eq(&self, other: &Self) -> bool {
+        self.children == other.children
+    }
+}
+
+impl ShapeModel for Group {
+    fn local_intersect(&self, _local_ray: &Ray) -> Vec<f64> {
+        // A group has no surface of its own; its children are intersected by
+        // `Shape::intersect`
+        vec![]
+    }
+
+    fn local_normal_at(&self, _local_point: Point) -> Vector {
+        unreachable!("groups have no surface to compute a normal for")
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.bvh().bounds()
+    }
+
+    fn children(&self) -> &[Shape] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut [Shape] {
+        self.bvh.take();
+        &mut self.children
+    }
+
+    fn candidate_children(&self, local_ray: &Ray) -> Vec<&Shape> {
+        self.bvh()
+            .candidates(local_ray)
+            .into_iter()
+            .map(|index| &self.children[index])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        matrices::IDENTITY,
+        shapes::{Cylinder, Sphere},
+        transformations::{scaling, translation},
+    };
+
+    use super::*;
+
+    #[test]
+    fn create_group() {
+        let g = Shape::new(Group::default());
+        assert_eq!(*g.get_transform(), IDENTITY);
+        assert!(g.model.children().is_empty());
+    }
+
+    #[test]
+    fn add_child_to_group() {
+        let s = Shape::new(Sphere);
+        let mut group = Group::default();
+        group.add_child(s.clone());
+        let g = Shape::new(group);
+        assert_eq!(g.model.children().len(), 1);
+        assert_eq!(g.model.children()[0], s);
+    }
+
+    #[test]
+    fn intersect_empty_group() {
+        let g = Shape::new(Group::default());
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert!(xs.is_empty());
+    }
+
+    #[test]
+    fn intersect_nonempty_group() {
+        let s1 = Shape::new(Sphere);
+        let mut s2 = Shape::new(Sphere);
+        s2.set_transform(translation(0.0, 0.0, -3.0)).unwrap();
+        let mut s3 = Shape::new(Sphere);
+        s3.set_transform(translation(5.0, 0.0, 0.0)).unwrap();
+        let g = Shape::new(Group::new(vec![s1, s2, s3]));
+        let children = g.model.children();
+
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 4);
+        assert!(std::ptr::eq(xs[0].object, &children[1]));
+        assert!(std::ptr::eq(xs[1].object, &children[1]));
+        assert!(std::ptr::eq(xs[2].object, &children[0]));
+        assert!(std::ptr::eq(xs[3].object, &children[0]));
+    }
+
+    #[test]
+    fn intersect_transformed_group() {
+        let mut s = Shape::new(Sphere);
+        s.set_transform(translation(5.0, 0.0, 0.0)).unwrap();
+        let mut g = Shape::new(Group::new(vec![s]));
+        g.set_transform(scaling(2.0, 2.0, 2.0)).unwrap();
+        let r = Ray::new(Point::new(10.0, 0.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 2);
+    }
+
+    #[test]
+    fn group_bounds_contain_children() {
+        let mut s = Shape::new(Sphere);
+        s.set_transform(&translation(2.0, 5.0, -3.0) * &scaling(2.0, 2.0, 2.0))
+            .unwrap();
+        let mut c = Shape::new(Cylinder::new(-2.0, 2.0, false));
+        c.set_transform(&translation(-4.0, -1.0, 4.0) * &scaling(0.5, 1.0, 0.5))
+            .unwrap();
+        let g = Group::new(vec![s, c]);
+        let b = g.bounds();
+        assert_eq!(b.min, Point::new(-4.5, -3.0, -5.0));
+        assert_eq!(b.max, Point::new(4.0, 7.0, 4.5));
+    }
+
+    #[test]
+    fn intersect_large_group() {
+        let mut group = Group::default();
+        for i in 0..100 {
+            let mut s = Shape::new(Sphere);
+            s.set_transform(translation(
+                (i % 10) as f64 * 3.0,
+                (i / 10) as f64 * 3.0,
+                0.0,
+            ))
+            .unwrap();
+            group.add_child(s);
+        }
+        let g = Shape::new(group);
+        let children = g.model.children();
+
+        let r = Ray::new(Point::new(6.0, 9.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert!(std::ptr::eq(xs[0].object, &children[32]));
+        assert_eq!(xs[0].t, 4.0);
+
+        let r = Ray::new(Point::new(-5.0, 3.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 20);
+        assert!(std::ptr::eq(xs[0].object, &children[10]));
+        assert!(std::ptr::eq(xs[19].object, &children[19]));
+    }
+
+    #[test]
+    fn moving_child_updates_group_bounds() {
+        let mut g = Shape::new(Group::new(vec![Shape::new(Sphere)]));
+        let r = Ray::new(Point::new(5.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(g.intersect(&r).is_empty());
+
+        g.model.children_mut()[0]
+            .set_transform(translation(5.0, 0.0, 0.0))
+            .unwrap();
+        assert_eq!(g.intersect(&r).len(), 2);
+        assert_eq!(g.model.bounds().max, Point::new(6.0, 1.0, 1.0));
+    }
+}
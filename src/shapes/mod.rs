@@ -1,24 +1,54 @@
 use crate::{
+    bounds::BoundingBox,
     intersections::{Intersection, Intersections},
     materials::Material,
     matrices::{Matrix, Transform, IDENTITY},
-    rays::Ray,
+    rays::{Ray, RayKind},
     Point, Vector,
 };
 
 use std::{any::Any, fmt::Debug};
 
+mod cones;
+mod csg;
+mod cubes;
+mod cylinders;
+mod groups;
 mod planes;
 mod spheres;
+mod triangles;
 
+pub use cones::Cone;
+pub use csg::{Csg, CsgOperation};
+pub use cubes::Cube;
+pub use cylinders::Cylinder;
+pub use groups::Group;
 pub use planes::Plane;
 pub use spheres::Sphere;
+pub use triangles::{SmoothTriangle, Triangle};
 
-pub trait DynamicShapeModel: Debug {
+pub trait DynamicShapeModel: Debug + Send + Sync {
     fn local_intersect(&self, local_ray: &Ray) -> Vec<f64>;
 
     fn local_normal_at(&self, local_point: Point) -> Vector;
 
+    fn bounds(&self) -> BoundingBox;
+
+    fn local_intersect_with_uv(&self, local_ray: &Ray) -> Vec<(f64, f64, f64)>;
+
+    fn local_normal_at_uv(&self, local_point: Point, u: f64, v: f64) -> Vector;
+
+    fn children(&self) -> &[Shape];
+
+    fn children_mut(&mut self) -> &mut [Shape];
+
+    fn candidate_children(&self, local_ray: &Ray) -> Vec<&Shape>;
+
+    fn filter_intersections<'shape>(
+        &self,
+        intersections: Intersections<'shape>,
+    ) -> Intersections<'shape>;
+
     fn as_any(&self) -> &dyn Any;
 
     fn dynamic_clone(&self) -> Box<dyn DynamicShapeModel>;
@@ -35,6 +65,37 @@ impl<T: ShapeModel> DynamicShapeModel for T {
         self.local_normal_at(local_point)
     }
 
+    fn bounds(&self) -> BoundingBox {
+        self.bounds()
+    }
+
+    fn local_intersect_with_uv(&self, local_ray: &Ray) -> Vec<(f64, f64, f64)> {
+        self.local_intersect_with_uv(local_ray)
+    }
+
+    fn local_normal_at_uv(&self, local_point: Point, u: f64, v: f64) -> Vector {
+        self.local_normal_at_uv(local_point, u, v)
+    }
+
+    fn children(&self) -> &[Shape] {
+        self.children()
+    }
+
+    fn children_mut(&mut self) -> &mut [Shape] {
+        self.children_mut()
+    }
+
+    fn candidate_children(&self, local_ray: &Ray) -> Vec<&Shape> {
+        self.candidate_children(local_ray)
+    }
+
+    fn filter_intersections<'shape>(
+        &self,
+        intersections: Intersections<'shape>,
+    ) -> Intersections<'shape> {
+        self.filter_intersections(intersections)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -52,18 +113,66 @@ impl<T: ShapeModel> DynamicShapeModel for T {
     }
 }
 
-pub trait ShapeModel: Clone + Debug + PartialEq + 'static {
+pub trait ShapeModel: Clone + Debug + PartialEq + Send + Sync + 'static {
     fn local_intersect(&self, local_ray: &Ray) -> Vec<f64>;
 
     fn local_normal_at(&self, local_point: Point) -> Vector;
+
+    /// The extent of the shape in object space
+    fn bounds(&self) -> BoundingBox;
+
+    /// Returns `(t, u, v)` for each intersection, where `u` and `v` are the
+    /// surface coordinates of the hit
+    fn local_intersect_with_uv(&self, local_ray: &Ray) -> Vec<(f64, f64, f64)> {
+        self.local_intersect(local_ray)
+            .into_iter()
+            .map(|t| (t, 0.0, 0.0))
+            .collect()
+    }
+
+    fn local_normal_at_uv(&self, local_point: Point, _u: f64, _v: f64) -> Vector {
+        self.local_normal_at(local_point)
+    }
+
+    /// Shapes nested inside this one, whose transforms are relative to it
+    fn children(&self) -> &[Shape] {
+        &[]
+    }
+
+    fn children_mut(&mut self) -> &mut [Shape] {
+        &mut []
+    }
+
+    /// Children that `local_ray` may intersect, in the order they appear in
+    /// `children`
+    fn candidate_children(&self, _local_ray: &Ray) -> Vec<&Shape> {
+        self.children().iter().collect()
+    }
+
+    /// Removes intersections that don't lie on the surface of the combined
+    /// shape, e.g. for constructive solid geometry
+    fn filter_intersections<'shape>(
+        &self,
+        intersections: Intersections<'shape>,
+    ) -> Intersections<'shape> {
+        intersections
+    }
 }
 
 #[derive(Debug)]
 pub struct Shape {
     transform: Transform,
     inverse: Transform,
+    /// Inverse of the combined transform of every enclosing group
+    parent_inverse: Transform,
+    /// Converts world space points directly to object space
+    world_inverse: Transform,
     pub material: Material,
     pub model: Box<dyn DynamicShapeModel>,
+    casts_shadow: bool,
+    receives_shadow: bool,
+    visible_to_camera: bool,
+    visible_in_reflections: bool,
 }
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
@@ -71,21 +180,100 @@ pub struct NoInverseError;
 
 impl Shape {
     pub fn new(model: impl ShapeModel) -> Self {
-        Shape {
+        let mut shape = Shape {
             transform: IDENTITY,
             inverse: IDENTITY,
+            parent_inverse: IDENTITY,
+            world_inverse: IDENTITY,
             material: Material::default(),
             model: Box::new(model),
-        }
+            casts_shadow: true,
+            receives_shadow: true,
+            visible_to_camera: true,
+            visible_in_reflections: true,
+        };
+        shape.update_children();
+        shape
     }
 
     pub fn set_transform(&mut self, transform: Transform) -> Result<(), NoInverseError> {
         let inverse = transform.inverse().ok_or(NoInverseError)?;
         self.transform = transform;
         self.inverse = inverse;
+        self.world_inverse = &self.inverse * &self.parent_inverse;
+        self.update_children();
         Ok(())
     }
 
+    fn set_parent_inverse(&mut self, parent_inverse: Transform) {
+        self.world_inverse = &self.inverse * &parent_inverse;
+        self.parent_inverse = parent_inverse;
+        self.update_children();
+    }
+
+    fn update_children(&mut self) {
+        let world_inverse = self.world_inverse.clone();
+        for child in self.model.children_mut() {
+            child.set_parent_inverse(world_inverse.clone());
+        }
+    }
+
+    pub fn casts_shadow(&self) -> bool {
+        self.casts_shadow
+    }
+
+    /// Sets whether the shape blocks light on its way to other surfaces
+    pub fn set_casts_shadow(&mut self, casts_shadow: bool) {
+        self.update_flags(&|shape| shape.casts_shadow = casts_shadow);
+    }
+
+    pub fn receives_shadow(&self) -> bool {
+        self.receives_shadow
+    }
+
+    /// Sets whether other shapes can darken this one by blocking its lights
+    pub fn set_receives_shadow(&mut self, receives_shadow: bool) {
+        self.update_flags(&|shape| shape.receives_shadow = receives_shadow);
+    }
+
+    pub fn visible_to_camera(&self) -> bool {
+        self.visible_to_camera
+    }
+
+    /// Sets whether camera rays, and rays refracted from them, can hit the
+    /// shape
+    pub fn set_visible_to_camera(&mut self, visible_to_camera: bool) {
+        self.update_flags(&|shape| shape.visible_to_camera = visible_to_camera);
+    }
+
+    pub fn visible_in_reflections(&self) -> bool {
+        self.visible_in_reflections
+    }
+
+    /// Sets whether the shape shows up in reflective surfaces
+    pub fn set_visible_in_reflections(&mut self, visible_in_reflections: bool) {
+        self.update_flags(&|shape| shape.visible_in_reflections = visible_in_reflections);
+    }
+
+    /// Whether a ray of the given kind can hit the shape. Shadow rays see
+    /// every shape, and leave it to the world to skip those that cast no
+    /// shadow.
+    pub fn is_visible_to(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.visible_to_camera,
+            RayKind::Reflection => self.visible_in_reflections,
+            RayKind::Shadow => true,
+        }
+    }
+
+    /// Applies `update` to this shape and every shape it currently contains
+    fn update_flags(&mut self, update: &impl Fn(&mut Shape)) {
+        update(self);
+        for child in self.model.children_mut() {
+            child.update_flags(update);
+        }
+    }
+
     pub fn get_transform(&self) -> &Transform {
         &self.transform
     }
@@ -94,24 +282,66 @@ impl Shape {
         &self.inverse
     }
 
-    pub fn intersect(&self, ray: &Ray) -> Intersections {
+    /// The extent of the shape in the space of the enclosing group (or the
+    /// world)
+    pub fn parent_space_bounds(&self) -> BoundingBox {
+        self.model.bounds().transformed(&self.transform)
+    }
+
+    /// Intersects `ray`, given in the space of the enclosing group (or the
+    /// world), with this shape and any of its children
+    pub fn intersect(&self, ray: &Ray) -> Intersections<'_> {
         let local_ray = ray.transformed(&self.inverse);
-        Intersections::new(
+        let intersections = Intersections::new(
             self.model
-                .local_intersect(&local_ray)
+                .local_intersect_with_uv(&local_ray)
                 .into_iter()
-                .map(|t| Intersection::new(t, self))
+                .map(|(t, u, v)| Intersection::with_uv(t, self, u, v))
+                .chain(
+                    self.model
+                        .candidate_children(&local_ray)
+                        .into_iter()
+                        .flat_map(|child| child.intersect(&local_ray)),
+                )
                 .collect(),
-        )
+        );
+        self.model.filter_intersections(intersections)
     }
 
+    /// Whether `other` is this shape or one of its descendants
+    pub fn includes(&self, other: &Shape) -> bool {
+        std::ptr::eq(self, other)
+            || self
+                .model
+                .children()
+                .iter()
+                .any(|child| child.includes(other))
+    }
+
+    /// Panics for groups and CSG shapes, which have no surface of their own
+    /// and are never the object of an intersection
     pub fn normal_at(&self, point: Point) -> Vector {
-        let local_point = &self.inverse * point;
+        let local_point = self.world_to_object(point);
         let local_normal = self.model.local_normal_at(local_point);
-        let local_normal_matrix =
-            Matrix::new([[local_normal.x], [local_normal.y], [local_normal.z]]);
+        self.normal_to_world(local_normal)
+    }
+
+    /// Like `normal_at`, but uses the surface coordinates of `hit` for shapes
+    /// that interpolate their normals
+    pub fn normal_at_hit(&self, point: Point, hit: &Intersection) -> Vector {
+        let local_point = self.world_to_object(point);
+        let local_normal = self.model.local_normal_at_uv(local_point, hit.u, hit.v);
+        self.normal_to_world(local_normal)
+    }
+
+    pub fn world_to_object(&self, point: Point) -> Point {
+        &self.world_inverse * point
+    }
+
+    pub fn normal_to_world(&self, normal: Vector) -> Vector {
+        let local_normal_matrix = Matrix::new([[normal.x], [normal.y], [normal.z]]);
         let world_normal_matrix = &self
-            .inverse
+            .world_inverse
             .submatrix(3, 3)
             .expect("matrix index error")
             .transpose()
@@ -130,8 +360,14 @@ impl Clone for Shape {
         Shape {
             transform: self.transform.clone(),
             inverse: self.inverse.clone(),
+            parent_inverse: self.parent_inverse.clone(),
+            world_inverse: self.world_inverse.clone(),
             material: self.material.clone(),
             model: self.model.dynamic_clone(),
+            casts_shadow: self.casts_shadow,
+            receives_shadow: self.receives_shadow,
+            visible_to_camera: self.visible_to_camera,
+            visible_in_reflections: self.visible_in_reflections,
         }
     }
 }
@@ -141,6 +377,10 @@ impl PartialEq for Shape {
         self.transform == other.transform
             && self.inverse == other.inverse
             && self.material == other.material
+            && self.casts_shadow == other.casts_shadow
+            && self.receives_shadow == other.receives_shadow
+            && self.visible_to_camera == other.visible_to_camera
+            && self.visible_in_reflections == other.visible_in_reflections
             && self.model.dynamic_eq(other.model.as_ref())
     }
 }
@@ -151,11 +391,12 @@ mod test {
 
     use crate::{
         matrices::IDENTITY,
-        transformations::{rotation_z, scaling, translation},
+        transformations::{rotation_y, rotation_z, scaling, translation},
         Point, Vector,
     };
 
     use super::*;
+    use crate::shapes::{Group, Sphere};
 
     #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
     struct TestModel;
@@ -168,6 +409,10 @@ mod test {
         fn local_normal_at(&self, local_point: Point) -> Vector {
             Vector::new(local_point.x, local_point.y, local_point.z)
         }
+
+        fn bounds(&self) -> BoundingBox {
+            BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+        }
     }
 
     #[test]
@@ -201,6 +446,57 @@ mod test {
         assert_eq!(s.material, m);
     }
 
+    #[test]
+    fn shapes_cast_shadows_by_default() {
+        let s = Shape::new(TestModel);
+        assert!(s.casts_shadow());
+    }
+
+    #[test]
+    fn group_shadow_casting_applies_to_children() {
+        let inner = Group::new(vec![Shape::new(Sphere)]);
+        let mut g = Shape::new(Group::new(vec![Shape::new(Sphere), Shape::new(inner)]));
+        g.set_casts_shadow(false);
+        assert!(!g.casts_shadow());
+        assert!(!g.model.children()[0].casts_shadow());
+        assert!(!g.model.children()[1].model.children()[0].casts_shadow());
+        g.set_casts_shadow(true);
+        assert!(g.model.children()[1].model.children()[0].casts_shadow());
+    }
+
+    #[test]
+    fn shapes_are_visible_to_every_ray_by_default() {
+        let s = Shape::new(TestModel);
+        assert!(s.receives_shadow());
+        assert!(s.is_visible_to(RayKind::Camera));
+        assert!(s.is_visible_to(RayKind::Reflection));
+        assert!(s.is_visible_to(RayKind::Shadow));
+    }
+
+    #[test]
+    fn visibility_follows_ray_kind() {
+        let mut s = Shape::new(TestModel);
+        s.set_visible_to_camera(false);
+        assert!(!s.is_visible_to(RayKind::Camera));
+        assert!(s.is_visible_to(RayKind::Reflection));
+        s.set_visible_in_reflections(false);
+        assert!(!s.is_visible_to(RayKind::Reflection));
+        assert!(s.is_visible_to(RayKind::Shadow));
+    }
+
+    #[test]
+    fn group_visibility_applies_to_children() {
+        let mut g = Shape::new(Group::new(vec![Shape::new(Sphere)]));
+        g.set_receives_shadow(false);
+        g.set_visible_to_camera(false);
+        g.set_visible_in_reflections(false);
+        let child = &g.model.children()[0];
+        assert!(!child.receives_shadow());
+        assert!(!child.visible_to_camera());
+        assert!(!child.visible_in_reflections());
+        assert!(child.casts_shadow());
+    }
+
     #[test]
     fn intersection_sets_object() {
         let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
@@ -252,4 +548,68 @@ mod test {
         let n = s.normal_at(Point::new(0.0, 2_f64.sqrt() / 2.0, -(2_f64.sqrt()) / 2.0));
         assert_eq!(n, Vector::new(0.0, 0.97014, -0.24254));
     }
+
+    fn nested_groups(scale: Transform) -> Shape {
+        let mut s = Shape::new(Sphere);
+        s.set_transform(translation(5.0, 0.0, 0.0)).unwrap();
+        let mut g2 = Shape::new(Group::new(vec![s]));
+        g2.set_transform(scale).unwrap();
+        let mut g1 = Shape::new(Group::new(vec![g2]));
+        g1.set_transform(rotation_y(PI / 2.0)).unwrap();
+        g1
+    }
+
+    #[test]
+    fn world_to_object_through_groups() {
+        let g1 = nested_groups(scaling(2.0, 2.0, 2.0));
+        let s = &g1.model.children()[0].model.children()[0];
+        let p = s.world_to_object(Point::new(-2.0, 0.0, -10.0));
+        assert_eq!(p, Point::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn normal_to_world_through_groups() {
+        let g1 = nested_groups(scaling(1.0, 2.0, 3.0));
+        let s = &g1.model.children()[0].model.children()[0];
+        let n = s.normal_to_world(Vector::new(
+            3_f64.sqrt() / 3.0,
+            3_f64.sqrt() / 3.0,
+            3_f64.sqrt() / 3.0,
+        ));
+        assert_eq!(n, Vector::new(0.28571, 0.42857, -0.85714));
+    }
+
+    #[test]
+    fn normal_on_child_object() {
+        let g1 = nested_groups(scaling(1.0, 2.0, 3.0));
+        let s = &g1.model.children()[0].model.children()[0];
+        let n = s.normal_at(Point::new(1.7321, 1.1547, -5.5774));
+        assert_eq!(n, Vector::new(0.2857, 0.42854, -0.85716));
+    }
+
+    #[test]
+    fn group_transform_set_after_nesting() {
+        let mut s = Shape::new(Sphere);
+        s.set_transform(translation(5.0, 0.0, 0.0)).unwrap();
+        let g2 = Shape::new(Group::new(vec![s]));
+        let mut g1 = Shape::new(Group::new(vec![g2]));
+        g1.set_transform(rotation_y(PI / 2.0)).unwrap();
+        g1.model.children_mut()[0]
+            .set_transform(scaling(2.0, 2.0, 2.0))
+            .unwrap();
+        assert_eq!(g1, nested_groups(scaling(2.0, 2.0, 2.0)));
+        let s = &g1.model.children()[0].model.children()[0];
+        let p = s.world_to_object(Point::new(-2.0, 0.0, -10.0));
+        assert_eq!(p, Point::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn shape_bounds_in_parent_space() {
+        let mut s = Shape::new(Sphere);
+        s.set_transform(&translation(1.0, -3.0, 5.0) * &scaling(0.5, 2.0, 4.0))
+            .unwrap();
+        let b = s.parent_space_bounds();
+        assert_eq!(b.min, Point::new(0.5, -5.0, 1.0));
+        assert_eq!(b.max, Point::new(1.5, -1.0, 9.0));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::Vector;
+use crate::{bounds::BoundingBox, Point, Vector};
 
 use super::ShapeModel;
 
@@ -20,11 +20,18 @@ impl ShapeModel for Plane {
     fn local_normal_at(&self, _local_point: crate::Point) -> crate::Vector {
         Vector::new(0.0, 1.0, 0.0)
     }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{rays::Ray, Point};
+    use crate::rays::Ray;
 
     use super::*;
 
@@ -39,6 +46,17 @@ mod test {
         assert_eq!(n3, Vector::new(0.0, 1.0, 0.0));
     }
 
+    #[test]
+    fn plane_bounds() {
+        let b = Plane.bounds();
+        assert_eq!(b.min.x, f64::NEG_INFINITY);
+        assert_eq!(b.min.y, 0.0);
+        assert_eq!(b.min.z, f64::NEG_INFINITY);
+        assert_eq!(b.max.x, f64::INFINITY);
+        assert_eq!(b.max.y, 0.0);
+        assert_eq!(b.max.z, f64::INFINITY);
+    }
+
     #[test]
     fn intersect_parallel_ray() {
         let p = Plane;
@@ -1,4 +1,4 @@
-use crate::{rays::Ray, Point, Vector};
+use crate::{bounds::BoundingBox, rays::Ray, Point, Vector};
 
 use super::{Shape, ShapeModel};
 
@@ -36,6 +36,10 @@ impl ShapeModel for Sphere {
     fn local_normal_at(&self, local_point: Point) -> Vector {
         local_point - Point::new(0.0, 0.0, 0.0)
     }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
 }
 
 #[cfg(test)]
@@ -142,6 +146,13 @@ mod test {
         assert_eq!(n, n.normalize());
     }
 
+    #[test]
+    fn sphere_bounds() {
+        let b = Sphere.bounds();
+        assert_eq!(b.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(b.max, Point::new(1.0, 1.0, 1.0));
+    }
+
     #[test]
     fn make_glass_sphere() {
         let s = Sphere::new_glass();
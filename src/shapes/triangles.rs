@@ -0,0 +1,340 @@
+use crate::{bounds::BoundingBox, rays::Ray, Point, Vector};
+
+use super::ShapeModel;
+
+const PARALLEL_EPSILON: f64 = 0.00001;
+
+/// A flat triangle with a single normal across its surface
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    p1: Point,
+    p2: Point,
+    p3: Point,
+    e1: Vector,
+    e2: Vector,
+    normal: Vector,
+}
+
+impl Triangle {
+    pub fn new(p1: Point, p2: Point, p3: Point) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        let normal = Vector::cross(e2, e1).normalize();
+        Triangle {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal,
+        }
+    }
+
+    pub fn p1(&self) -> Point {
+        self.p1
+    }
+
+    pub fn p2(&self) -> Point {
+        self.p2
+    }
+
+    pub fn p3(&self) -> Point {
+        self.p3
+    }
+
+    pub fn e1(&self) -> Vector {
+        self.e1
+    }
+
+    pub fn e2(&self) -> Vector {
+        self.e2
+    }
+
+    pub fn normal(&self) -> Vector {
+        self.normal
+    }
+}
+
+/// Möller–Trumbore ray-triangle intersection, returning `(t, u, v)` on a hit
+fn intersect_triangle(
+    p1: Point,
+    e1: Vector,
+    e2: Vector,
+    local_ray: &Ray,
+) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = Vector::cross(local_ray.direction, e2);
+    let det = Vector::dot(e1, dir_cross_e2);
+    // `det` scales with the size of the triangle, so the threshold does too
+    let scale = e1.magnitude() * e2.magnitude() * local_ray.direction.magnitude();
+    if det.abs() < scale * PARALLEL_EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = local_ray.origin - p1;
+    let u = f * Vector::dot(p1_to_origin, dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = Vector::cross(p1_to_origin, e1);
+    let v = f * Vector::dot(local_ray.direction, origin_cross_e1);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = f * Vector::dot(e2, origin_cross_e1);
+    Some((t, u, v))
+}
+
+fn triangle_bounds(p1: Point, p2: Point, p3: Point) -> BoundingBox {
+    let mut bounds = BoundingBox::empty();
+    bounds.add_point(p1);
+    bounds.add_point(p2);
+    bounds.add_point(p3);
+    bounds
+}
+
+impl ShapeModel for Triangle {
+    fn local_intersect(&self, local_ray: &Ray) -> Vec<f64> {
+        intersect_triangle(self.p1, self.e1, self.e2, local_ray)
+            .map(|(t, _, _)| t)
+            .into_iter()
+            .collect()
+    }
+
+    fn local_normal_at(&self, _local_point: Point) -> Vector {
+        self.normal
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+}
+
+/// A triangle whose normal is interpolated between the normals at each vertex
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothTriangle {
+    p1: Point,
+    p2: Point,
+    p3: Point,
+    n1: Vector,
+    n2: Vector,
+    n3: Vector,
+    e1: Vector,
+    e2: Vector,
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Point, p2: Point, p3: Point, n1: Vector, n2: Vector, n3: Vector) -> Self {
+        SmoothTriangle {
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+        }
+    }
+
+    pub fn p1(&self) -> Point {
+        self.p1
+    }
+
+    pub fn p2(&self) -> Point {
+        self.p2
+    }
+
+    pub fn p3(&self) -> Point {
+        self.p3
+    }
+
+    pub fn n1(&self) -> Vector {
+        self.n1
+    }
+
+    pub fn n2(&self) -> Vector {
+        self.n2
+    }
+
+    pub fn n3(&self) -> Vector {
+        self.n3
+    }
+}
+
+impl ShapeModel for SmoothTriangle {
+    fn local_intersect(&self, local_ray: &Ray) -> Vec<f64> {
+        self.local_intersect_with_uv(local_ray)
+            .into_iter()
+            .map(|(t, _, _)| t)
+            .collect()
+    }
+
+    fn local_normal_at(&self, _local_point: Point) -> Vector {
+        // Without the surface coordinates of a hit, fall back to the flat
+        // normal of the triangle
+        Vector::cross(self.e2, self.e1).normalize()
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+
+    fn local_intersect_with_uv(&self, local_ray: &Ray) -> Vec<(f64, f64, f64)> {
+        intersect_triangle(self.p1, self.e1, self.e2, local_ray)
+            .into_iter()
+            .collect()
+    }
+
+    fn local_normal_at_uv(&self, _local_point: Point, u: f64, v: f64) -> Vector {
+        self.n2 * u + self.n3 * v + self.n1 * (1.0 - u - v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        intersections::{HitInfo, Intersection, Intersections},
+        shapes::Shape,
+        EQUALITY_EPSILON,
+    };
+
+    use super::*;
+
+    fn test_triangle() -> Triangle {
+        Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    fn test_smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(-1.0, 0.0, 0.0),
+            Vector::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn construct_triangle() {
+        let t = test_triangle();
+        assert_eq!(t.p1(), Point::new(0.0, 1.0, 0.0));
+        assert_eq!(t.p2(), Point::new(-1.0, 0.0, 0.0));
+        assert_eq!(t.p3(), Point::new(1.0, 0.0, 0.0));
+        assert_eq!(t.e1(), Vector::new(-1.0, -1.0, 0.0));
+        assert_eq!(t.e2(), Vector::new(1.0, -1.0, 0.0));
+        assert_eq!(t.normal(), Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn normal_on_triangle() {
+        let t = test_triangle();
+        let n1 = t.local_normal_at(Point::new(0.0, 0.5, 0.0));
+        let n2 = t.local_normal_at(Point::new(-0.5, 0.75, 0.0));
+        let n3 = t.local_normal_at(Point::new(0.5, 0.25, 0.0));
+        assert_eq!(n1, t.normal());
+        assert_eq!(n2, t.normal());
+        assert_eq!(n3, t.normal());
+    }
+
+    #[test]
+    fn intersect_parallel_ray() {
+        let t = test_triangle();
+        let r = Ray::new(Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 0.0));
+        assert!(t.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_misses_edges() {
+        let t = test_triangle();
+        let origins = [
+            Point::new(1.0, 1.0, -2.0),
+            Point::new(-1.0, 1.0, -2.0),
+            Point::new(0.0, -1.0, -2.0),
+        ];
+        for origin in origins {
+            let r = Ray::new(origin, Vector::new(0.0, 0.0, 1.0));
+            assert!(t.local_intersect(&r).is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_strikes_triangle() {
+        let t = test_triangle();
+        let r = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = t.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0], 2.0);
+    }
+
+    #[test]
+    fn ray_strikes_small_triangle() {
+        let t = Triangle::new(
+            Point::new(0.0, 0.002, 0.0),
+            Point::new(-0.001, 0.0, 0.0),
+            Point::new(0.001, 0.0, 0.0),
+        );
+        let r = Ray::new(Point::new(0.0, 0.001, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = t.local_intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0], 2.0);
+    }
+
+    #[test]
+    fn triangle_bounds() {
+        let t = Triangle::new(
+            Point::new(-3.0, 7.0, 2.0),
+            Point::new(6.0, 2.0, -4.0),
+            Point::new(2.0, -1.0, -1.0),
+        );
+        let b = t.bounds();
+        assert_eq!(b.min, Point::new(-3.0, -1.0, -4.0));
+        assert_eq!(b.max, Point::new(6.0, 7.0, 2.0));
+    }
+
+    #[test]
+    fn construct_smooth_triangle() {
+        let tri = test_smooth_triangle();
+        assert_eq!(tri.p1(), Point::new(0.0, 1.0, 0.0));
+        assert_eq!(tri.p2(), Point::new(-1.0, 0.0, 0.0));
+        assert_eq!(tri.p3(), Point::new(1.0, 0.0, 0.0));
+        assert_eq!(tri.n1(), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(tri.n2(), Vector::new(-1.0, 0.0, 0.0));
+        assert_eq!(tri.n3(), Vector::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn smooth_triangle_intersection_stores_uv() {
+        let tri = Shape::new(test_smooth_triangle());
+        let r = Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = tri.intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert!((xs[0].u - 0.45).abs() < EQUALITY_EPSILON);
+        assert!((xs[0].v - 0.25).abs() < EQUALITY_EPSILON);
+    }
+
+    #[test]
+    fn smooth_triangle_interpolates_normal() {
+        let tri = Shape::new(test_smooth_triangle());
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let n = tri.normal_at_hit(Point::new(0.0, 0.0, 0.0), &i);
+        assert_eq!(n, Vector::new(-0.5547, 0.83205, 0.0));
+    }
+
+    #[test]
+    fn prepare_normal_on_smooth_triangle() {
+        let tri = Shape::new(test_smooth_triangle());
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let r = Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![i]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        assert_eq!(hit_info.normal, Vector::new(-0.5547, 0.83205, 0.0));
+    }
+}
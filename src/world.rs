@@ -1,19 +1,26 @@
+use std::sync::OnceLock;
+
 use crate::{
-    canvas::Color,
+    bounds::BoundingBox,
+    bvh::Bvh,
+    canvas::{Color, BLACK, WHITE},
     intersections::{HitInfo, Intersection, Intersections},
-    lights::PointLight,
+    lights::{Light, LightSample},
     materials::lighting,
-    rays::Ray,
+    rays::{Ray, RayKind},
     shapes::Shape,
-    Point,
+    Point, Vector,
 };
 
 pub const RECURSION_DEPTH: usize = 5;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone)]
 pub struct World {
-    pub objects: Vec<Shape>,
-    pub light: PointLight,
+    objects: Vec<Shape>,
+    pub lights: Vec<Light>,
+    bvh_disabled: bool,
+    /// Hierarchy over `objects`, cleared by every method that can change them
+    bvh: OnceLock<Bvh>,
 }
 
 impl World {
@@ -21,30 +28,105 @@ impl World {
         World::default()
     }
 
-    pub fn intersect(&self, ray: &Ray) -> Intersections {
-        let vec = self
-            .objects
-            .iter()
-            .flat_map(|object| object.intersect(ray).into_iter())
-            .collect::<Vec<Intersection>>();
-        Intersections::new(vec)
+    pub fn objects(&self) -> &[Shape] {
+        &self.objects
+    }
+
+    pub fn objects_mut(&mut self) -> &mut [Shape] {
+        self.bvh.take();
+        &mut self.objects
+    }
+
+    pub fn add_object(&mut self, object: Shape) {
+        self.bvh.take();
+        self.objects.push(object);
+    }
+
+    /// Whether `intersect` uses a bounding volume hierarchy, which is built
+    /// on first use. Enabled by default; disabling it makes `intersect` test
+    /// every object, e.g. to measure what the hierarchy saves.
+    pub fn set_bvh_enabled(&mut self, enabled: bool) {
+        self.bvh_disabled = !enabled;
+    }
+
+    fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let bounds = self
+                .objects
+                .iter()
+                .map(|object| object.parent_space_bounds())
+                .collect::<Vec<_>>();
+            Bvh::new(&bounds)
+        })
+    }
+
+    /// Intersects `ray` with every object that rays of its kind can see
+    pub fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let vec = if self.bvh_disabled {
+            self.objects
+                .iter()
+                .flat_map(|object| object.intersect(ray).into_iter())
+                .collect::<Vec<Intersection>>()
+        } else {
+            self.bvh()
+                .candidates(ray)
+                .into_iter()
+                .flat_map(|index| self.objects[index].intersect(ray).into_iter())
+                .collect::<Vec<Intersection>>()
+        };
+        Intersections::new(
+            vec.into_iter()
+                .filter(|intersection| intersection.object.is_visible_to(ray.kind))
+                .collect(),
+        )
     }
 
+    /// The extent of every object in the world
+    pub fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for object in &self.objects {
+            bounds.merge(&object.parent_space_bounds());
+        }
+        bounds
+    }
+
+    /// Shades a hit by adding up the contribution of each light, followed by
+    /// any reflection and refraction. Objects that do not receive shadows get
+    /// the full intensity of every light.
     pub fn shade_hit(&self, hit_info: &HitInfo, remaining: usize) -> Color {
-        let is_shadowed = self.is_shadowed(hit_info.over_point);
-        let surface = lighting(
-            &hit_info.object.material,
-            hit_info.object,
-            &self.light,
-            hit_info.point,
-            hit_info.eyev,
-            hit_info.normal,
-            is_shadowed,
-        );
+        let surface = self
+            .lights
+            .iter()
+            .map(|light| {
+                // The shadow test and the shading use the same light samples
+                let samples = light.samples(hit_info.point);
+                let intensity = if hit_info.object.receives_shadow() {
+                    self.intensity_at(&samples, hit_info.over_point)
+                } else {
+                    WHITE
+                };
+                lighting(
+                    &hit_info.object.material,
+                    hit_info.object,
+                    &samples,
+                    hit_info.point,
+                    hit_info.eyev,
+                    hit_info.normal,
+                    intensity,
+                )
+            })
+            .fold(Color::default(), |sum, color| sum + color);
 
         let reflected = self.reflected_color(hit_info, remaining);
+        let refracted = self.refracted_color(hit_info, remaining);
 
-        surface + reflected
+        let material = &hit_info.object.material;
+        if material.reflective > 0.0 && material.transparaency > 0.0 {
+            let reflectance = hit_info.schlick();
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
     }
 
     pub fn color_from(&self, ray: &Ray, remaining: usize) -> Color {
@@ -56,18 +138,52 @@ impl World {
         self.shade_hit(&hit_info, remaining)
     }
 
-    pub fn is_shadowed(&self, point: Point) -> bool {
-        let light_to_point = self.light.position - point;
-        let distance = light_to_point.magnitude();
-        let direction = light_to_point.normalize();
+    /// The share of the light from `samples` that reaches `point`. It is
+    /// white when nothing is in the way and black in full shadow.
+    pub fn intensity_at(&self, samples: &[LightSample], point: Point) -> Color {
+        let count = samples.len().max(1) as f64;
+        samples
+            .iter()
+            .map(|sample| self.light_transmitted(point, sample))
+            .fold(Color::default(), |sum, color| sum + color)
+            * (1.0 / count)
+    }
 
-        let ray = Ray::new(point, direction);
+    /// The share of the light `sample` comes from that gets through to
+    /// `point`. Objects block it unless their material has
+    /// `transparent_shadows`, in which case they let their `transparaency` of
+    /// it through at each surface crossed, tinted by their color if the
+    /// material asks for it. Shapes that do not cast shadows are ignored, and
+    /// for lights with no position, anything along the light's direction is
+    /// in the way.
+    pub fn light_transmitted(&self, point: Point, sample: &LightSample) -> Color {
+        let ray = Ray::with_kind(point, sample.direction, RayKind::Shadow);
         let intersections = self.intersect(&ray);
-        if let Some(hit_index) = intersections.hit() {
-            intersections[hit_index].t < distance
-        } else {
-            false
+        let mut transmitted = WHITE;
+        for intersection in intersections
+            .iter()
+            .filter(|intersection| intersection.t >= 0.0 && intersection.t < sample.distance)
+        {
+            let object = intersection.object;
+            if !object.casts_shadow() {
+                continue;
+            }
+            let material = &object.material;
+            if !material.transparent_shadows || material.transparaency == 0.0 {
+                return BLACK;
+            }
+            transmitted = transmitted * material.transparaency;
+            if material.tinted_shadows {
+                let surface_point = ray.position(intersection.t);
+                transmitted = transmitted * material.color_at(object, surface_point);
+            }
         }
+        transmitted
+    }
+
+    /// Whether the light `sample` comes from is completely blocked at `point`
+    pub fn is_shadowed(&self, point: Point, sample: &LightSample) -> bool {
+        self.light_transmitted(point, sample) == BLACK
     }
 
     pub fn reflected_color(&self, hit_info: &HitInfo, remaining: usize) -> Color {
@@ -75,18 +191,50 @@ impl World {
             return Color::new(0.0, 0.0, 0.0);
         }
 
-        let reflect_ray = Ray::new(hit_info.over_point, hit_info.reflectv);
+        let reflect_ray =
+            Ray::with_kind(hit_info.over_point, hit_info.reflectv, RayKind::Reflection);
         let color = self.color_from(&reflect_ray, remaining - 1);
 
         color * hit_info.object.material.reflective
     }
+
+    pub fn refracted_color(&self, hit_info: &HitInfo, remaining: usize) -> Color {
+        if remaining == 0 || hit_info.object.material.transparaency == 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        // Snell's law: sin(theta_t) = n1 / n2 * sin(theta_i)
+        let n_ratio = hit_info.n1 / hit_info.n2;
+        let cos_i = Vector::dot(hit_info.eyev, hit_info.normal);
+        let sin2_t = n_ratio * n_ratio * (1.0 - cos_i * cos_i);
+        if sin2_t > 1.0 {
+            // Total internal reflection
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = hit_info.normal * (n_ratio * cos_i - cos_t) - hit_info.eyev * n_ratio;
+        let refract_ray = Ray::with_kind(hit_info.under_point, direction, hit_info.ray_kind);
+        let color = self.color_from(&refract_ray, remaining - 1);
+
+        color * hit_info.object.material.transparaency
+    }
+}
+
+impl PartialEq for World {
+    fn eq(&self, other: &Self) -> bool {
+        self.objects == other.objects && self.lights == other.lights
+    }
 }
 
 #[cfg(test)]
 pub(crate) fn default_world() -> World {
-    use crate::{shapes::Sphere, transformations::Builder};
+    use crate::{lights::PointLight, shapes::Sphere, transformations::Builder};
 
-    let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+    let light = Light::new(PointLight::new(
+        Point::new(-10.0, 10.0, -10.0),
+        Color::new(1.0, 1.0, 1.0),
+    ));
     let mut s1 = Shape::new(Sphere);
     s1.material.color = Color::new(0.8, 1.0, 0.6);
     s1.material.diffuse = 0.7;
@@ -96,7 +244,8 @@ pub(crate) fn default_world() -> World {
         .unwrap();
     World {
         objects: vec![s1, s2],
-        light,
+        lights: vec![light],
+        ..World::default()
     }
 }
 
@@ -104,24 +253,49 @@ pub(crate) fn default_world() -> World {
 mod test {
     use crate::{
         canvas::Color,
+        lights::{AreaLight, DirectionalLight, PointLight},
+        patterns::{Pattern, TestPattern},
         rays::Ray,
         shapes::{Plane, Sphere},
         transformations::{translation, Builder},
-        Point, Vector,
+        Point,
     };
 
     use super::*;
 
+    const LIGHT_POSITION: Point = Point {
+        x: -10.0,
+        y: 10.0,
+        z: -10.0,
+    };
+
+    fn is_shadowed_from(w: &World, light_position: Point, point: Point) -> bool {
+        let sample = LightSample::from_position(point, light_position, Color::new(1.0, 1.0, 1.0));
+        w.is_shadowed(point, &sample)
+    }
+
+    fn intensity_from(w: &World, light: &Light, point: Point) -> Color {
+        w.intensity_at(&light.samples(point), point)
+    }
+
+    fn transmitted_from(w: &World, light_position: Point, point: Point) -> Color {
+        let sample = LightSample::from_position(point, light_position, Color::new(1.0, 1.0, 1.0));
+        w.light_transmitted(point, &sample)
+    }
+
     #[test]
     fn create_world() {
         let w = World::new();
-        assert!(w.objects.is_empty());
-        assert_eq!(w.light, PointLight::default());
+        assert!(w.objects().is_empty());
+        assert!(w.lights.is_empty());
     }
 
     #[test]
     fn test_default_world() {
-        let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let light = Light::new(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ));
         let mut s1 = Shape::new(Sphere);
         s1.material.color = Color::new(0.8, 1.0, 0.6);
         s1.material.diffuse = 0.7;
@@ -131,9 +305,9 @@ mod test {
             .unwrap();
 
         let w = default_world();
-        assert_eq!(w.light, light);
-        assert!(w.objects.contains(&s1));
-        assert!(w.objects.contains(&s2));
+        assert_eq!(w.lights, vec![light]);
+        assert!(w.objects().contains(&s1));
+        assert!(w.objects().contains(&s2));
     }
 
     #[test]
@@ -152,7 +326,7 @@ mod test {
     fn shading_intersection() {
         let w = default_world();
         let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
-        let shape = &w.objects[0];
+        let shape = &w.objects()[0];
         let i = Intersection::new(4.0, shape);
         let xs = Intersections::new(vec![i.clone()]);
         let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
@@ -163,9 +337,12 @@ mod test {
     #[test]
     fn shading_intersection_from_inside() {
         let mut w = default_world();
-        w.light = PointLight::new(Point::new(0.0, 0.25, 0.0), Color::new(1.0, 1.0, 1.0));
+        w.lights = vec![Light::new(PointLight::new(
+            Point::new(0.0, 0.25, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
         let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
-        let shape = &w.objects[1];
+        let shape = &w.objects()[1];
         let i = Intersection::new(0.5, shape);
         let xs = Intersections::new(vec![i.clone()]);
         let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
@@ -192,11 +369,11 @@ mod test {
     #[test]
     fn color_with_intersection_behind_ray() {
         let mut w = default_world();
-        let outer = &mut w.objects[0];
+        let outer = &mut w.objects_mut()[0];
         outer.material.ambient = 1.0;
-        let inner = &mut w.objects[1];
+        let inner = &mut w.objects_mut()[1];
         inner.material.ambient = 1.0;
-        let inner = &w.objects[1];
+        let inner = &w.objects()[1];
         let r = Ray::new(Point::new(0.0, 0.0, 0.75), Vector::new(0.0, 0.0, -1.0));
         let c = w.color_from(&r, RECURSION_DEPTH);
         assert_eq!(c, inner.material.color);
@@ -206,54 +383,105 @@ mod test {
     fn no_object_on_line_shadow() {
         let w = default_world();
         let p = Point::new(0.0, 10.0, 0.0);
-        assert!(!w.is_shadowed(p));
+        assert!(!is_shadowed_from(&w, LIGHT_POSITION, p));
     }
 
     #[test]
     fn object_between_shadow() {
         let w = default_world();
         let p = Point::new(10.0, -10.0, 10.0);
-        assert!(w.is_shadowed(p));
+        assert!(is_shadowed_from(&w, LIGHT_POSITION, p));
     }
 
     #[test]
     fn object_behind_light_shadow() {
         let w = default_world();
         let p = Point::new(-20.0, 20.0, -20.0);
-        assert!(!w.is_shadowed(p));
+        assert!(!is_shadowed_from(&w, LIGHT_POSITION, p));
     }
 
     #[test]
     fn object_other_side_shadow() {
         let w = default_world();
         let p = Point::new(-2.0, 2.0, -2.0);
-        assert!(!w.is_shadowed(p));
+        assert!(!is_shadowed_from(&w, LIGHT_POSITION, p));
     }
 
     #[test]
     fn shade_hit_given_shadowed() {
         let mut w = World::new();
-        w.light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        w.lights = vec![Light::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
         let s1 = Shape::new(Sphere);
-        w.objects.push(s1);
+        w.add_object(s1);
         let mut s2 = Shape::new(Sphere);
         s2.set_transform(translation(0.0, 0.0, 10.0)).unwrap();
-        w.objects.push(s2);
+        w.add_object(s2);
         let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
-        let i = Intersection::new(4.0, &w.objects[1]);
+        let i = Intersection::new(4.0, &w.objects()[1]);
         let xs = Intersections::new(vec![i.clone()]);
         let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
         let c = w.shade_hit(&hit_info, RECURSION_DEPTH);
         assert_eq!(c, Color::new(0.1, 0.1, 0.1));
     }
 
+    #[test]
+    fn shade_hit_adds_up_lights() {
+        let mut w = default_world();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let single = w.color_from(&r, RECURSION_DEPTH);
+        w.lights.push(w.lights[0].clone());
+        let c = w.color_from(&r, RECURSION_DEPTH);
+        assert_eq!(c, single * 2.0);
+        w.lights.clear();
+        let c = w.color_from(&r, RECURSION_DEPTH);
+        assert_eq!(c, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn shade_hit_shadowed_from_one_light() {
+        let mut w = World::new();
+        let white = Color::new(1.0, 1.0, 1.0);
+        let key = Point::new(0.0, 0.0, -10.0);
+        let fill = Point::new(10.0, 0.0, -10.0);
+        w.lights = vec![
+            Light::new(PointLight::new(key, white)),
+            Light::new(PointLight::new(fill, white * 0.5)),
+        ];
+        w.add_object(Shape::new(Sphere));
+        let mut s2 = Shape::new(Sphere);
+        s2.set_transform(translation(0.0, 0.0, 10.0)).unwrap();
+        w.add_object(s2);
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, &w.objects()[1]);
+        let xs = Intersections::new(vec![i.clone()]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        assert!(is_shadowed_from(&w, key, hit_info.over_point));
+        assert!(!is_shadowed_from(&w, fill, hit_info.over_point));
+
+        // Only ambient light is left from the first light
+        let unshadowed = lighting(
+            &w.objects()[1].material,
+            &w.objects()[1],
+            &w.lights[1].samples(hit_info.point),
+            hit_info.point,
+            hit_info.eyev,
+            hit_info.normal,
+            WHITE,
+        );
+        let c = w.shade_hit(&hit_info, RECURSION_DEPTH);
+        assert_eq!(c, Color::new(0.1, 0.1, 0.1) + unshadowed);
+    }
+
     #[test]
     fn reflected_color_nonreflective_material() {
         let mut w = default_world();
         let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
-        let shape = &mut w.objects[1];
+        let shape = &mut w.objects_mut()[1];
         shape.material.ambient = 1.0;
-        let shape = &w.objects[1];
+        let shape = &w.objects()[1];
         let i = Intersection::new(1.0, shape);
         let xs = Intersections::new(vec![i.clone()]);
         let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
@@ -267,8 +495,8 @@ mod test {
         let mut shape = Shape::new(Plane);
         shape.material.reflective = 0.5;
         shape.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
-        w.objects.push(shape);
-        let shape = &w.objects[2];
+        w.add_object(shape);
+        let shape = &w.objects()[2];
         let r = Ray::new(
             Point::new(0.0, 0.0, -3.0),
             Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
@@ -286,8 +514,8 @@ mod test {
         let mut shape = Shape::new(Plane);
         shape.material.reflective = 0.5;
         shape.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
-        w.objects.push(shape);
-        let shape = &w.objects[2];
+        w.add_object(shape);
+        let shape = &w.objects()[2];
         let r = Ray::new(
             Point::new(0.0, 0.0, -3.0),
             Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
@@ -302,15 +530,18 @@ mod test {
     #[test]
     fn mutually_reflective_surfaces() {
         let mut w = World::new();
-        w.light = PointLight::new(Point::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        w.lights = vec![Light::new(PointLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
         let mut lower = Shape::new(Plane);
         lower.material.reflective = 1.0;
         lower.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
-        w.objects.push(lower);
+        w.add_object(lower);
         let mut upper = Shape::new(Plane);
         upper.material.reflective = 1.0;
         upper.set_transform(translation(0.0, 1.0, 0.0)).unwrap();
-        w.objects.push(upper);
+        w.add_object(upper);
         let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
         w.color_from(&r, RECURSION_DEPTH);
     }
@@ -321,8 +552,8 @@ mod test {
         let mut shape = Shape::new(Plane);
         shape.material.reflective = 0.5;
         shape.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
-        w.objects.push(shape);
-        let shape = &w.objects[2];
+        w.add_object(shape);
+        let shape = &w.objects()[2];
         let r = Ray::new(
             Point::new(0.0, 0.0, -3.0),
             Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
@@ -333,4 +564,573 @@ mod test {
         let color = w.reflected_color(&hit_info, 0);
         assert_eq!(color, Color::new(0.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn refracted_color_opaque_surface() {
+        let w = default_world();
+        let shape = &w.objects()[0];
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(4.0, shape),
+            Intersection::new(6.0, shape),
+        ]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let color = w.refracted_color(&hit_info, RECURSION_DEPTH);
+        assert_eq!(color, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn refracted_color_at_max_recursive_depth() {
+        let mut w = default_world();
+        let shape = &mut w.objects_mut()[0];
+        shape.material.transparaency = 1.0;
+        shape.material.refractive_index = 1.5;
+        let shape = &w.objects()[0];
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(4.0, shape),
+            Intersection::new(6.0, shape),
+        ]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let color = w.refracted_color(&hit_info, 0);
+        assert_eq!(color, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn shade_hit_with_transparent_material() {
+        let mut w = default_world();
+        let mut floor = Shape::new(Plane);
+        floor.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
+        floor.material.transparaency = 0.5;
+        floor.material.refractive_index = 1.5;
+        w.add_object(floor);
+        let mut ball = Shape::new(Sphere);
+        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5)).unwrap();
+        w.add_object(ball);
+        let floor = &w.objects()[2];
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
+        );
+        let xs = Intersections::new(vec![Intersection::new(2_f64.sqrt(), floor)]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let color = w.shade_hit(&hit_info, RECURSION_DEPTH);
+        assert_eq!(color, Color::new(0.93642, 0.68642, 0.68642));
+    }
+
+    #[test]
+    fn shade_hit_with_reflective_transparent_material() {
+        let mut w = default_world();
+        let mut floor = Shape::new(Plane);
+        floor.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
+        floor.material.reflective = 0.5;
+        floor.material.transparaency = 0.5;
+        floor.material.refractive_index = 1.5;
+        w.add_object(floor);
+        let mut ball = Shape::new(Sphere);
+        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5)).unwrap();
+        w.add_object(ball);
+        let floor = &w.objects()[2];
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
+        );
+        let xs = Intersections::new(vec![Intersection::new(2_f64.sqrt(), floor)]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let color = w.shade_hit(&hit_info, RECURSION_DEPTH);
+        assert_eq!(color, Color::new(0.93391, 0.69643, 0.69243));
+    }
+
+    #[test]
+    fn shade_hit_with_transparent_shadows() {
+        let mut w = default_world();
+        let mut floor = Shape::new(Plane);
+        floor.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
+        floor.material.transparaency = 0.5;
+        floor.material.refractive_index = 1.5;
+        floor.material.transparent_shadows = true;
+        w.add_object(floor);
+        let mut ball = Shape::new(Sphere);
+        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5)).unwrap();
+        w.add_object(ball);
+        let floor = &w.objects()[2];
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
+        );
+        let xs = Intersections::new(vec![Intersection::new(2_f64.sqrt(), floor)]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let color = w.shade_hit(&hit_info, RECURSION_DEPTH);
+        // The floor lets half the light through to the ball below, which
+        // makes the ball redder than with solid shadows
+        assert_eq!(color, Color::new(1.12547, 0.68643, 0.68643));
+    }
+
+    #[test]
+    fn shade_hit_with_reflective_transparent_shadows() {
+        let mut w = default_world();
+        let mut floor = Shape::new(Plane);
+        floor.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
+        floor.material.reflective = 0.5;
+        floor.material.transparaency = 0.5;
+        floor.material.refractive_index = 1.5;
+        floor.material.transparent_shadows = true;
+        w.add_object(floor);
+        let mut ball = Shape::new(Sphere);
+        ball.material.color = Color::new(1.0, 0.0, 0.0);
+        ball.material.ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5)).unwrap();
+        w.add_object(ball);
+        let floor = &w.objects()[2];
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
+        );
+        let xs = Intersections::new(vec![Intersection::new(2_f64.sqrt(), floor)]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let color = w.shade_hit(&hit_info, RECURSION_DEPTH);
+        assert_eq!(color, Color::new(1.115, 0.69643, 0.69243));
+    }
+
+    #[test]
+    fn refracted_color_under_total_internal_reflection() {
+        let mut w = default_world();
+        let shape = &mut w.objects_mut()[0];
+        shape.material.transparaency = 1.0;
+        shape.material.refractive_index = 1.5;
+        let shape = &w.objects()[0];
+        let r = Ray::new(
+            Point::new(0.0, 0.0, 2_f64.sqrt() / 2.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        let xs = Intersections::new(vec![
+            Intersection::new(-(2_f64.sqrt()) / 2.0, shape),
+            Intersection::new(2_f64.sqrt() / 2.0, shape),
+        ]);
+        let hit_info = HitInfo::prepare(&xs, &r, 1).unwrap();
+        let color = w.refracted_color(&hit_info, RECURSION_DEPTH);
+        assert_eq!(color, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn refracted_color_with_refracted_ray() {
+        let mut w = default_world();
+        let a = &mut w.objects_mut()[0];
+        a.material.ambient = 1.0;
+        a.material.pattern = Some(Pattern::new(TestPattern));
+        let b = &mut w.objects_mut()[1];
+        b.material.transparaency = 1.0;
+        b.material.refractive_index = 1.5;
+        let (a, b) = (&w.objects()[0], &w.objects()[1]);
+        let r = Ray::new(Point::new(0.0, 0.0, 0.1), Vector::new(0.0, 1.0, 0.0));
+        let xs = Intersections::new(vec![
+            Intersection::new(-0.9899, a),
+            Intersection::new(-0.4899, b),
+            Intersection::new(0.4899, b),
+            Intersection::new(0.9899, a),
+        ]);
+        let hit_info = HitInfo::prepare(&xs, &r, 2).unwrap();
+        let color = w.refracted_color(&hit_info, RECURSION_DEPTH);
+        assert_eq!(color, Color::new(0.0, 0.99888, 0.04725));
+    }
+
+    #[test]
+    fn world_bounds() {
+        let w = default_world();
+        let b = w.bounds();
+        assert_eq!(b.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(b.max, Point::new(1.0, 1.0, 1.0));
+
+        let mut w = World::new();
+        w.add_object(Shape::new(Plane));
+        assert!(!w.bounds().is_finite());
+    }
+
+    #[test]
+    fn intersect_world_with_bvh() {
+        let mut w = default_world();
+        for i in 0..50 {
+            let mut s = Shape::new(Sphere);
+            s.set_transform(translation(i as f64 * 3.0, 5.0, 0.0))
+                .unwrap();
+            w.add_object(s);
+        }
+        w.add_object(Shape::new(Plane));
+        let mut linear = w.clone();
+        linear.set_bvh_enabled(false);
+
+        let rays = [
+            Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            Ray::new(Point::new(-5.0, 5.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            Ray::new(Point::new(30.0, 10.0, -5.0), Vector::new(0.0, -1.0, 0.5)),
+        ];
+        for r in rays {
+            let expected = linear.intersect(&r);
+            let xs = w.intersect(&r);
+            assert_eq!(xs.len(), expected.len());
+            for (x, e) in xs.iter().zip(expected.iter()) {
+                assert_eq!(x.t, e.t);
+                assert_eq!(x.object, e.object);
+            }
+        }
+    }
+
+    #[test]
+    fn bvh_is_rebuilt_after_adding_objects() {
+        let mut w = default_world();
+        let r = Ray::new(Point::new(5.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(w.intersect(&r).is_empty());
+        let mut s = Shape::new(Sphere);
+        s.set_transform(translation(5.0, 0.0, 0.0)).unwrap();
+        w.add_object(s);
+        assert_eq!(w.intersect(&r).len(), 2);
+    }
+
+    #[test]
+    fn bvh_is_rebuilt_after_moving_objects() {
+        let mut w = default_world();
+        let r = Ray::new(Point::new(5.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert!(w.intersect(&r).is_empty());
+        w.objects_mut()[0]
+            .set_transform(translation(5.0, 0.0, 0.0))
+            .unwrap();
+        assert_eq!(w.intersect(&r).len(), 2);
+    }
+
+    #[test]
+    fn point_light_intensity() {
+        let w = default_world();
+        let light = &w.lights[0];
+        let cases = [
+            (Point::new(0.0, 1.0001, 0.0), 1.0),
+            (Point::new(-1.0001, 0.0, 0.0), 1.0),
+            (Point::new(0.0, 0.0, -1.0001), 1.0),
+            (Point::new(0.0, 0.0, 1.0001), 0.0),
+            (Point::new(1.0001, 0.0, 0.0), 0.0),
+            (Point::new(0.0, -1.0001, 0.0), 0.0),
+            (Point::new(0.0, 0.0, 0.0), 0.0),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(intensity_from(&w, light, point), WHITE * expected);
+        }
+    }
+
+    #[test]
+    fn area_light_intensity() {
+        let mut w = default_world();
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        let mut area = AreaLight::new(corner, v1, 2, v2, 2, Color::new(1.0, 1.0, 1.0)).unwrap();
+        area.jitter = false;
+        w.lights = vec![Light::new(area)];
+        let light = &w.lights[0];
+        let cases = [
+            (Point::new(0.0, 0.0, 2.0), 0.0),
+            (Point::new(1.0, -1.0, 2.0), 0.25),
+            (Point::new(1.5, 0.0, 2.0), 0.5),
+            (Point::new(1.25, 1.25, 3.0), 0.75),
+            (Point::new(0.0, 0.0, -2.0), 1.0),
+        ];
+        for (point, expected) in cases {
+            assert_eq!(intensity_from(&w, light, point), WHITE * expected);
+        }
+    }
+
+    #[test]
+    fn area_light_softens_shadow_edges() {
+        let mut w = default_world();
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        w.lights = vec![Light::new(
+            AreaLight::new(corner, v1, 8, v2, 8, Color::new(1.0, 1.0, 1.0)).unwrap(),
+        )];
+        let light = &w.lights[0];
+        assert_eq!(intensity_from(&w, light, Point::new(0.0, 0.0, 2.0)), BLACK);
+        assert_eq!(intensity_from(&w, light, Point::new(0.0, 0.0, -2.0)), WHITE);
+        let penumbra = intensity_from(&w, light, Point::new(1.5, 0.0, 2.0)).difference(&BLACK);
+        assert!(penumbra > 0.25 && penumbra < 0.75);
+    }
+
+    #[test]
+    fn shade_hit_shades_and_shadows_with_same_light_samples() {
+        let mut w = default_world();
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        w.lights = vec![Light::new(
+            AreaLight::new(corner, v1, 8, v2, 8, Color::new(1.0, 1.0, 1.0)).unwrap(),
+        )];
+        let mut wall = Shape::new(Plane);
+        wall.set_transform(
+            Builder::new()
+                .rotation_x(std::f64::consts::FRAC_PI_2)
+                .translation(0.0, 0.0, 2.0)
+                .transform(),
+        )
+        .unwrap();
+        w.add_object(wall);
+        let r = Ray::new(Point::new(1.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+        let hit_info = HitInfo::prepare(&xs, &r, xs.hit().unwrap()).unwrap();
+
+        let samples = w.lights[0].samples(hit_info.point);
+        let intensity = w.intensity_at(&samples, hit_info.over_point);
+        let share = intensity.difference(&BLACK);
+        assert!(share > 0.25 && share < 0.75);
+        let expected = lighting(
+            &hit_info.object.material,
+            hit_info.object,
+            &samples,
+            hit_info.point,
+            hit_info.eyev,
+            hit_info.normal,
+            intensity,
+        );
+        assert_eq!(w.shade_hit(&hit_info, RECURSION_DEPTH), expected);
+    }
+
+    #[test]
+    fn directional_light_shadows_reach_any_distance() {
+        let mut w = World::new();
+        w.lights = vec![Light::new(DirectionalLight::new(
+            Vector::new(0.0, -1.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let mut blocker = Shape::new(Sphere);
+        blocker.set_transform(translation(0.0, 1e6, 0.0)).unwrap();
+        w.add_object(blocker);
+        let light = &w.lights[0];
+        assert_eq!(intensity_from(&w, light, Point::new(0.0, 0.0, 0.0)), BLACK);
+        assert_eq!(intensity_from(&w, light, Point::new(2.0, 0.0, 0.0)), WHITE);
+        assert_eq!(intensity_from(&w, light, Point::new(0.0, 2e6, 0.0)), WHITE);
+    }
+
+    #[test]
+    fn directional_light_matches_distant_point_light() {
+        let mut w = default_world();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        w.lights = vec![Light::new(PointLight::new(
+            Point::new(-1e7, 1e7, -1e7),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let distant = w.color_from(&r, RECURSION_DEPTH);
+        w.lights = vec![Light::new(DirectionalLight::new(
+            Vector::new(1.0, -1.0, 1.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let sun = w.color_from(&r, RECURSION_DEPTH);
+        assert_eq!(sun, distant);
+    }
+
+    #[test]
+    fn transparent_objects_cast_solid_shadows_by_default() {
+        let mut w = World::new();
+        let mut glass = Shape::new(Sphere);
+        glass.material.transparaency = 0.5;
+        w.add_object(glass);
+        let light = Point::new(0.0, 0.0, -10.0);
+        let p = Point::new(0.0, 0.0, 10.0);
+        assert_eq!(transmitted_from(&w, light, p), BLACK);
+        assert!(is_shadowed_from(&w, light, p));
+    }
+
+    #[test]
+    fn transparent_shadows_let_light_through() {
+        let mut w = World::new();
+        let mut glass = Shape::new(Sphere);
+        glass.material.transparaency = 0.5;
+        glass.material.transparent_shadows = true;
+        w.add_object(glass);
+        let light = Point::new(0.0, 0.0, -10.0);
+        let p = Point::new(0.0, 0.0, 10.0);
+
+        // The shadow ray crosses the sphere's surface twice
+        assert_eq!(transmitted_from(&w, light, p), Color::new(0.25, 0.25, 0.25));
+        assert!(!is_shadowed_from(&w, light, p));
+        assert_eq!(
+            transmitted_from(&w, light, Point::new(0.0, 0.0, 0.0)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+
+        // An opaque object anywhere along the way still blocks everything
+        let mut wall = Shape::new(Sphere);
+        wall.set_transform(translation(0.0, 0.0, 5.0)).unwrap();
+        w.add_object(wall);
+        assert_eq!(transmitted_from(&w, light, p), BLACK);
+        assert!(is_shadowed_from(&w, light, p));
+    }
+
+    #[test]
+    fn tinted_shadows_take_material_color() {
+        let mut w = World::new();
+        let mut glass = Shape::new(Sphere);
+        glass.material.transparaency = 0.8;
+        glass.material.color = Color::new(1.0, 0.5, 0.0);
+        glass.material.transparent_shadows = true;
+        w.add_object(glass);
+        let light = Point::new(0.0, 0.0, -10.0);
+        let p = Point::new(0.0, 0.0, 10.0);
+        assert_eq!(transmitted_from(&w, light, p), Color::new(0.64, 0.64, 0.64));
+        w.objects_mut()[0].material.tinted_shadows = true;
+        assert_eq!(transmitted_from(&w, light, p), Color::new(0.64, 0.16, 0.0));
+    }
+
+    #[test]
+    fn shapes_can_opt_out_of_casting_shadows() {
+        let mut w = default_world();
+        let p = Point::new(10.0, -10.0, 10.0);
+        assert!(is_shadowed_from(&w, LIGHT_POSITION, p));
+        for object in w.objects_mut() {
+            object.set_casts_shadow(false);
+        }
+        assert!(!is_shadowed_from(&w, LIGHT_POSITION, p));
+        assert_eq!(transmitted_from(&w, LIGHT_POSITION, p), WHITE);
+
+        // The objects themselves are still seen and lit
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let c = w.color_from(&r, RECURSION_DEPTH);
+        assert_eq!(c, Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn glass_casts_lighter_shadow_than_opaque_sphere() {
+        let mut w = World::new();
+        w.lights = vec![Light::new(PointLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let mut floor = Shape::new(Plane);
+        floor.set_transform(translation(0.0, -2.0, 0.0)).unwrap();
+        w.add_object(floor);
+        w.add_object(Shape::new(Sphere));
+        let r = Ray::new(
+            Point::new(0.0, -1.5, -5.0),
+            Vector::new(0.0, -0.5, 5.0).normalize(),
+        );
+        let opaque = w.color_from(&r, RECURSION_DEPTH);
+        let glass = &mut w.objects_mut()[1];
+        *glass = Sphere::new_glass();
+        glass.material.transparaency = 0.9;
+        glass.material.transparent_shadows = true;
+        let glass = w.color_from(&r, RECURSION_DEPTH);
+        assert_eq!(opaque, Color::new(0.1, 0.1, 0.1));
+        assert!(glass.difference(&opaque) > 0.5);
+    }
+
+    #[test]
+    fn hidden_objects_still_cast_shadows() {
+        let mut w = default_world();
+        for object in w.objects_mut() {
+            object.set_visible_to_camera(false);
+        }
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(w.color_from(&r, RECURSION_DEPTH), BLACK);
+        assert!(is_shadowed_from(
+            &w,
+            LIGHT_POSITION,
+            Point::new(10.0, -10.0, 10.0)
+        ));
+    }
+
+    /// The default world above a mirror, with the ray from
+    /// `reflected_color_reflective_material`
+    fn mirror_world_color(w: &mut World) -> Color {
+        let mut mirror = Shape::new(Plane);
+        mirror.material.reflective = 0.5;
+        mirror.set_transform(translation(0.0, -1.0, 0.0)).unwrap();
+        w.add_object(mirror);
+        let r = Ray::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vector::new(0.0, -(2_f64.sqrt()) / 2.0, 2_f64.sqrt() / 2.0),
+        );
+        let i = Intersection::new(2_f64.sqrt(), &w.objects()[2]);
+        let xs = Intersections::new(vec![i]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        w.reflected_color(&hit_info, RECURSION_DEPTH)
+    }
+
+    #[test]
+    fn objects_hidden_from_camera_show_in_reflections() {
+        let mut w = default_world();
+        for object in w.objects_mut() {
+            object.set_visible_to_camera(false);
+        }
+        let color = mirror_world_color(&mut w);
+        assert_eq!(color, Color::new(0.19032, 0.2379, 0.14274));
+    }
+
+    #[test]
+    fn objects_hidden_from_reflections_do_not_show_in_mirrors() {
+        let mut w = default_world();
+        for object in w.objects_mut() {
+            object.set_visible_in_reflections(false);
+        }
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            w.color_from(&r, RECURSION_DEPTH),
+            Color::new(0.38066, 0.47583, 0.2855)
+        );
+        assert_eq!(mirror_world_color(&mut w), BLACK);
+    }
+
+    #[test]
+    fn refracted_rays_keep_ray_kind() {
+        let mut w = World::new();
+        w.lights = default_world().lights;
+        let mut glass = Shape::new(Sphere);
+        glass.material.transparaency = 1.0;
+        glass.material.refractive_index = 1.5;
+        w.add_object(glass);
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let without_back = w.color_from(&r, RECURSION_DEPTH);
+
+        let mut back = Shape::new(Plane);
+        back.set_transform(
+            Builder::new()
+                .rotation_x(std::f64::consts::FRAC_PI_2)
+                .translation(0.0, 0.0, 5.0)
+                .transform(),
+        )
+        .unwrap();
+        w.add_object(back);
+        assert_ne!(w.color_from(&r, RECURSION_DEPTH), without_back);
+        w.objects_mut()[1].set_visible_to_camera(false);
+        assert_eq!(w.color_from(&r, RECURSION_DEPTH), without_back);
+    }
+
+    #[test]
+    fn shapes_can_opt_out_of_receiving_shadows() {
+        let mut w = World::new();
+        w.lights = vec![Light::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            WHITE,
+        ))];
+        w.add_object(Shape::new(Sphere));
+        let mut s2 = Shape::new(Sphere);
+        s2.set_transform(translation(0.0, 0.0, 10.0)).unwrap();
+        s2.set_receives_shadow(false);
+        w.add_object(s2);
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = Intersections::new(vec![Intersection::new(4.0, &w.objects()[1])]);
+        let hit_info = HitInfo::prepare(&xs, &r, 0).unwrap();
+        let unshadowed = lighting(
+            &w.objects()[1].material,
+            &w.objects()[1],
+            &w.lights[0].samples(hit_info.point),
+            hit_info.point,
+            hit_info.eyev,
+            hit_info.normal,
+            WHITE,
+        );
+        assert_eq!(w.shade_hit(&hit_info, RECURSION_DEPTH), unshadowed);
+        assert_ne!(unshadowed, Color::new(0.1, 0.1, 0.1));
+    }
 }